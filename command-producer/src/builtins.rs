@@ -0,0 +1,253 @@
+use std::cell::RefCell;
+use std::io::{Read, Write};
+use std::rc::Rc;
+
+use crate::command::Command;
+use crate::command_processor::CommandProcessor;
+use crate::environment::Environment;
+use crate::exit_status::ExitStatus;
+use crate::job::JobStatus;
+use crate::job_manager::JobManager;
+
+/// Lists every tracked job, reaping finished ones first so the listing
+/// reflects reality (`[1] Running  sleep 10 &`), matching a real shell's
+/// `jobs` output.
+pub struct JobsBuiltin {
+    job_manager: Rc<RefCell<JobManager>>,
+}
+
+impl JobsBuiltin {
+    pub fn new(job_manager: Rc<RefCell<JobManager>>) -> Self {
+        Self { job_manager }
+    }
+}
+
+impl CommandProcessor for JobsBuiltin {
+    fn process(
+        &self,
+        _command: &Command,
+        _stdin: &mut dyn Read,
+        stdout: &mut dyn Write,
+        _stderr: &mut dyn Write,
+    ) -> Result<ExitStatus, Box<dyn std::error::Error>> {
+        let mut manager = self.job_manager.borrow_mut();
+        manager.reap_finished();
+        for job in manager.jobs() {
+            let status = match job.status {
+                JobStatus::Running => "Running".to_string(),
+                JobStatus::Stopped => "Stopped".to_string(),
+                JobStatus::Done(code) => format!("Done({code})"),
+            };
+            writeln!(stdout, "[{}] {}  {}", job.id, status, job.command_line)?;
+        }
+        Ok(ExitStatus::success())
+    }
+}
+
+/// `fg %N` (or `fg N`): blocks until job `N`'s pipeline finishes and reports
+/// its exit status, the same convention `execute_pipeline` uses for a
+/// foreground pipeline's own status.
+pub struct FgBuiltin {
+    job_manager: Rc<RefCell<JobManager>>,
+}
+
+impl FgBuiltin {
+    pub fn new(job_manager: Rc<RefCell<JobManager>>) -> Self {
+        Self { job_manager }
+    }
+}
+
+impl CommandProcessor for FgBuiltin {
+    fn process(
+        &self,
+        command: &Command,
+        _stdin: &mut dyn Read,
+        stdout: &mut dyn Write,
+        stderr: &mut dyn Write,
+    ) -> Result<ExitStatus, Box<dyn std::error::Error>> {
+        let Some(arg) = command.Args.first() else {
+            writeln!(stderr, "fg: usage: fg %N")?;
+            return Ok(ExitStatus::from_code(1));
+        };
+        let id_str = arg.strip_prefix('%').unwrap_or(arg);
+        let Ok(id) = id_str.parse::<u32>() else {
+            writeln!(stderr, "fg: invalid job id: {arg}")?;
+            return Ok(ExitStatus::from_code(1));
+        };
+
+        match self.job_manager.borrow_mut().foreground(id) {
+            Some(code) => {
+                writeln!(stdout, "[{id}] Done")?;
+                Ok(ExitStatus::from_code(code))
+            }
+            None => {
+                writeln!(stderr, "fg: no such job: {id}")?;
+                Ok(ExitStatus::from_code(1))
+            }
+        }
+    }
+}
+
+/// `bg %N`: confirms job `N` is running in the background. This crate never
+/// suspends a job into `JobStatus::Stopped` (there's no signal handling to
+/// drive it), so unlike a real shell's `bg` this can't resume a stopped job
+/// — it only reports the job's `[N] command &` line, matching what a
+/// genuine resume would print once the job was already running.
+pub struct BgBuiltin {
+    job_manager: Rc<RefCell<JobManager>>,
+}
+
+impl BgBuiltin {
+    pub fn new(job_manager: Rc<RefCell<JobManager>>) -> Self {
+        Self { job_manager }
+    }
+}
+
+impl CommandProcessor for BgBuiltin {
+    fn process(
+        &self,
+        command: &Command,
+        _stdin: &mut dyn Read,
+        stdout: &mut dyn Write,
+        stderr: &mut dyn Write,
+    ) -> Result<ExitStatus, Box<dyn std::error::Error>> {
+        let Some(arg) = command.Args.first() else {
+            writeln!(stderr, "bg: usage: bg %N")?;
+            return Ok(ExitStatus::from_code(1));
+        };
+        let id_str = arg.strip_prefix('%').unwrap_or(arg);
+        let Ok(id) = id_str.parse::<u32>() else {
+            writeln!(stderr, "bg: invalid job id: {arg}")?;
+            return Ok(ExitStatus::from_code(1));
+        };
+
+        let manager = self.job_manager.borrow();
+        match manager.jobs().iter().find(|job| job.id == id) {
+            Some(job) => {
+                writeln!(stdout, "[{}] {}", job.id, job.command_line)?;
+                Ok(ExitStatus::success())
+            }
+            None => {
+                writeln!(stderr, "bg: no such job: {id}")?;
+                Ok(ExitStatus::from_code(1))
+            }
+        }
+    }
+}
+
+/// `wait [%N]`: blocks until job `N` (or, with no argument, every tracked
+/// job) finishes, reporting the last one's exit code — the same
+/// foreground-blocking `JobManager::foreground` does for `fg`, just without
+/// `fg`'s `[id] Done` announcement.
+pub struct WaitBuiltin {
+    job_manager: Rc<RefCell<JobManager>>,
+}
+
+impl WaitBuiltin {
+    pub fn new(job_manager: Rc<RefCell<JobManager>>) -> Self {
+        Self { job_manager }
+    }
+}
+
+impl CommandProcessor for WaitBuiltin {
+    fn process(
+        &self,
+        command: &Command,
+        _stdin: &mut dyn Read,
+        _stdout: &mut dyn Write,
+        stderr: &mut dyn Write,
+    ) -> Result<ExitStatus, Box<dyn std::error::Error>> {
+        if let Some(arg) = command.Args.first() {
+            let id_str = arg.strip_prefix('%').unwrap_or(arg);
+            let Ok(id) = id_str.parse::<u32>() else {
+                writeln!(stderr, "wait: invalid job id: {arg}")?;
+                return Ok(ExitStatus::from_code(1));
+            };
+            return match self.job_manager.borrow_mut().foreground(id) {
+                Some(code) => Ok(ExitStatus::from_code(code)),
+                None => {
+                    writeln!(stderr, "wait: no such job: {id}")?;
+                    Ok(ExitStatus::from_code(1))
+                }
+            };
+        }
+
+        let ids: Vec<u32> = self
+            .job_manager
+            .borrow()
+            .jobs()
+            .iter()
+            .map(|job| job.id)
+            .collect();
+        let mut code = 0;
+        for id in ids {
+            if let Some(c) = self.job_manager.borrow_mut().foreground(id) {
+                code = c;
+            }
+        }
+        Ok(ExitStatus::from_code(code))
+    }
+}
+
+/// `export NAME=value`: parses the `=` and sets the variable in the shared
+/// `Environment`, so the next external process `run_external` spawns sees
+/// it via `env_clear`/`envs`.
+pub struct ExportBuiltin {
+    environment: Rc<RefCell<Environment>>,
+}
+
+impl ExportBuiltin {
+    pub fn new(environment: Rc<RefCell<Environment>>) -> Self {
+        Self { environment }
+    }
+}
+
+impl CommandProcessor for ExportBuiltin {
+    fn process(
+        &self,
+        command: &Command,
+        _stdin: &mut dyn Read,
+        _stdout: &mut dyn Write,
+        stderr: &mut dyn Write,
+    ) -> Result<ExitStatus, Box<dyn std::error::Error>> {
+        let Some(arg) = command.Args.first() else {
+            writeln!(stderr, "export: usage: export NAME=value")?;
+            return Ok(ExitStatus::from_code(1));
+        };
+        let Some((name, value)) = arg.split_once('=') else {
+            writeln!(stderr, "export: invalid assignment: {arg}")?;
+            return Ok(ExitStatus::from_code(1));
+        };
+        self.environment.borrow_mut().set(name, value);
+        Ok(ExitStatus::success())
+    }
+}
+
+/// `unset NAME`: removes the variable from the shared `Environment`, so
+/// subsequent `export`ed-process spawns no longer see it.
+pub struct UnsetBuiltin {
+    environment: Rc<RefCell<Environment>>,
+}
+
+impl UnsetBuiltin {
+    pub fn new(environment: Rc<RefCell<Environment>>) -> Self {
+        Self { environment }
+    }
+}
+
+impl CommandProcessor for UnsetBuiltin {
+    fn process(
+        &self,
+        command: &Command,
+        _stdin: &mut dyn Read,
+        _stdout: &mut dyn Write,
+        stderr: &mut dyn Write,
+    ) -> Result<ExitStatus, Box<dyn std::error::Error>> {
+        let Some(name) = command.Args.first() else {
+            writeln!(stderr, "unset: usage: unset NAME")?;
+            return Ok(ExitStatus::from_code(1));
+        };
+        self.environment.borrow_mut().remove(name);
+        Ok(ExitStatus::success())
+    }
+}
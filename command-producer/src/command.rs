@@ -1,7 +1,20 @@
+use crate::redirect::Redirect;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Command {
     pub Name: String,
     pub Args: Vec<String>,
+    /// Every `N>`/`N>>`/`N<`/`N>&M` redirection on this command, in the
+    /// order it was written — see `Redirect` for why order matters.
+    pub Redirects: Vec<Redirect>,
+    /// The body of a `<<DELIM`/`<<<word` heredoc or here-string, already
+    /// collected and newline-terminated — takes the place of whatever
+    /// stdin a pipeline stage would otherwise receive. `None` means this
+    /// command's stdin comes from the previous stage (or a `Redirects`
+    /// entry on fd 0) as usual.
     pub Stdin: Option<String>,
-    pub Stdout: Option<String>,
+    /// Set when the line ended in a bare, trailing `&` — the whole
+    /// pipeline this command is the last stage of should run asynchronously
+    /// under a `JobManager` rather than block `ExecutePipeline`'s caller.
+    pub Background: bool,
 }
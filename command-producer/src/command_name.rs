@@ -0,0 +1,27 @@
+/// A command's dispatch key in a `CommandProcessorMap` — just the program
+/// name (`"echo"`, `"ls"`), wrapped so the map can't be confused with one
+/// keyed by something else.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CommandName(String);
+
+impl CommandName {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for CommandName {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl From<String> for CommandName {
+    fn from(s: String) -> Self {
+        Self::new(s)
+    }
+}
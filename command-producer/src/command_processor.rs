@@ -1,10 +1,24 @@
 use std::collections::HashMap;
+use std::io::{Read, Write};
 
 use crate::command::Command;
 use crate::command_name::CommandName;
+use crate::exit_status::ExitStatus;
 
+/// A builtin's execution contract: given a parsed `Command`, read whatever
+/// it needs from `stdin`, write its output to `stdout`/`stderr`, and report
+/// what happened as an `ExitStatus` — the same shape `execute_pipeline`
+/// gets back from an external process, so a builtin and a spawned binary
+/// can sit on either side of the same pipe without the caller knowing which
+/// kind it talked to.
 pub trait CommandProcessor {
-    fn process(&self, command: &Command) -> Result<(), Box<dyn std::error::Error>>;
+    fn process(
+        &self,
+        command: &Command,
+        stdin: &mut dyn Read,
+        stdout: &mut dyn Write,
+        stderr: &mut dyn Write,
+    ) -> Result<ExitStatus, Box<dyn std::error::Error>>;
 }
 
 pub type CommandProcessorMap = HashMap<CommandName, Box<dyn CommandProcessor>>;
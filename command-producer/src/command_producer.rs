@@ -1,42 +1,288 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::error::Error;
+use std::iter::Peekable;
+use std::rc::Rc;
+use std::vec::IntoIter;
 
 use crate::command::Command;
 use crate::command_name::CommandName;
 use crate::command_processor::CommandProcessorMap;
+use crate::environment::{Environment, EXIT_STATUS_KEY};
+use crate::exit_status::ExitStatus;
+use crate::job_manager::JobManager;
+use crate::pipeline;
+use crate::redirect::Redirect;
 
 pub struct CommandProducer {
     processors: HashMap<CommandName, Box<dyn crate::command_processor::CommandProcessor>>,
+    environment: Rc<RefCell<Environment>>,
+    job_manager: Rc<RefCell<JobManager>>,
 }
 
 impl CommandProducer {
     pub fn new() -> Self {
         Self {
             processors: HashMap::new(),
+            environment: Rc::new(RefCell::new(Environment::new())),
+            job_manager: Rc::new(RefCell::new(JobManager::new())),
         }
     }
 
+    /// The shell variables `export`/`unset` (and `ExecutePipeline`'s calls
+    /// into `run_external`) share — cloning the handle lets a caller build
+    /// `ExportBuiltin`/`UnsetBuiltin` instances wired to this same state.
+    pub fn environment(&self) -> Rc<RefCell<Environment>> {
+        Rc::clone(&self.environment)
+    }
+
+    /// The job table `jobs`/`fg`/`bg`/`wait` builtins (and `ExecutePipeline`,
+    /// for a trailing-`&` pipeline) share — cloning the handle lets a caller
+    /// build those builtins wired to this same state.
+    pub fn job_manager(&self) -> Rc<RefCell<JobManager>> {
+        Rc::clone(&self.job_manager)
+    }
+
+    /// `input` is consumed as a single stream of physical lines rather than
+    /// mapped line-by-line, because a `<<DELIM` heredoc on one line pulls
+    /// its body out of the lines that follow it.
     pub fn ProduceCommands(&mut self, input: Vec<String>) -> Vec<Command> {
-        input.into_iter().map(|cmd_str| {
-            let parts: Vec<String> = cmd_str.split_whitespace().map(|s| s.to_string()).collect();
-            if parts.is_empty() {
-                Command {
-                    Name: String::new(),
-                    Args: vec![],
-                    Stdin: None,
-                    Stdout: None,
-                }
-            } else {
-                Command {
-                    Name: parts[0].clone(),
-                    Args: parts[1..].to_vec(),
-                    Stdin: None,
-                    Stdout: None,
-                }
+        let mut lines = input.into_iter().peekable();
+        let mut commands = Vec::new();
+        while let Some(cmd_str) = lines.next() {
+            commands.push(Self::parse_command(&cmd_str, &mut lines));
+        }
+        commands
+    }
+
+    /// Splits `cmd_str` on whitespace, then walks the resulting words
+    /// picking out redirection operators (`N>`, `N>>`, `N<`, `N>&M`,
+    /// `&>file`) and `<<DELIM`/`<<<word` heredoc operators, routing
+    /// everything else into `Args` — the word right after the command name
+    /// is always the first argument candidate, so redirections are
+    /// recognized starting there. `lines` is the iterator of remaining
+    /// physical lines, consumed when a heredoc body needs to be collected.
+    fn parse_command(cmd_str: &str, lines: &mut Peekable<IntoIter<String>>) -> Command {
+        let mut parts = cmd_str
+            .split_whitespace()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+            .peekable();
+
+        let Some(name) = parts.next() else {
+            return Command {
+                Name: String::new(),
+                Args: vec![],
+                Redirects: vec![],
+                Stdin: None,
+                Background: false,
+            };
+        };
+
+        let mut args = Vec::new();
+        let mut redirects = Vec::new();
+        let mut stdin = None;
+        let mut background = false;
+        while let Some(word) = parts.next() {
+            if word == "&" && parts.peek().is_none() {
+                background = true;
+                continue;
+            }
+            if let Some(heredoc_stdin) = Self::parse_heredoc_word(&word, &mut parts, lines) {
+                stdin = Some(heredoc_stdin);
+                continue;
+            }
+            match Self::parse_redirect_word(&word, &mut parts) {
+                Some(parsed) => redirects.extend(parsed),
+                None => args.push(word),
+            }
+        }
+
+        Command {
+            Name: name,
+            Args: args,
+            Redirects: redirects,
+            Stdin: stdin,
+            Background: background,
+        }
+    }
+
+    /// Recognizes `<<<word` (a here-string, `word` expanded verbatim plus a
+    /// trailing newline) and `<<DELIM` (a heredoc: every subsequent line
+    /// from `lines`, accumulated verbatim with a trailing newline each,
+    /// until a line exactly equal to `DELIM`). A quoted delimiter
+    /// (`<<'EOF'`) only has its quotes stripped for the purpose of matching
+    /// the terminator — this crate has no expansion pass of its own to
+    /// suppress, unlike the Expander-backed input pipeline.
+    fn parse_heredoc_word(
+        word: &str,
+        rest: &mut Peekable<IntoIter<String>>,
+        lines: &mut Peekable<IntoIter<String>>,
+    ) -> Option<String> {
+        if let Some(body) = word.strip_prefix("<<<") {
+            let raw = Self::take_target(body, rest)?;
+            return Some(format!("{}\n", Self::strip_quotes(&raw)));
+        }
+
+        let body = word.strip_prefix("<<")?;
+        let raw_delim = Self::take_target(body, rest)?;
+        let delim = Self::strip_quotes(&raw_delim);
+
+        let mut collected = String::new();
+        for line in lines.by_ref() {
+            if line == delim {
+                break;
             }
-        }).collect()
+            collected.push_str(&line);
+            collected.push('\n');
+        }
+        Some(collected)
+    }
+
+    /// Strips one layer of matching leading/trailing `'`/`"` quotes, as in
+    /// a heredoc delimiter written `<<'EOF'` or `<<"EOF"`.
+    fn strip_quotes(word: &str) -> String {
+        let bytes = word.as_bytes();
+        if bytes.len() >= 2 {
+            let (first, last) = (bytes[0], bytes[bytes.len() - 1]);
+            if first == last && (first == b'\'' || first == b'"') {
+                return word[1..word.len() - 1].to_string();
+            }
+        }
+        word.to_string()
+    }
+
+    /// Parses one redirection starting at `word`, pulling the target
+    /// filename from `rest` when `word` is a bare operator like `2>` with
+    /// the path as its own word (`2> out.txt`). Returns `None` for a word
+    /// that isn't a redirection at all, so the caller treats it as a plain
+    /// argument.
+    fn parse_redirect_word(word: &str, rest: &mut Peekable<IntoIter<String>>) -> Option<Vec<Redirect>> {
+        if let Some(body) = word.strip_prefix("&>>") {
+            let path = Self::take_target(body, rest)?;
+            return Some(vec![
+                Redirect::to_file(1, path, true, false),
+                Redirect::dup_fd(2, 1),
+            ]);
+        }
+        if let Some(body) = word.strip_prefix("&>") {
+            let path = Self::take_target(body, rest)?;
+            return Some(vec![
+                Redirect::to_file(1, path, false, false),
+                Redirect::dup_fd(2, 1),
+            ]);
+        }
+
+        let digit_len = word.chars().take_while(|c| c.is_ascii_digit()).count();
+        let (fd_digits, operator_and_body) = word.split_at(digit_len);
+
+        let (is_append, is_input, body) = if let Some(b) = operator_and_body.strip_prefix(">>") {
+            (true, false, b)
+        } else if let Some(b) = operator_and_body.strip_prefix('>') {
+            (false, false, b)
+        } else if let Some(b) = operator_and_body.strip_prefix('<') {
+            if b.starts_with('<') {
+                // `<<`/`<<<` are heredoc/here-string operators, not a file
+                // redirection this parser handles — leave the word alone.
+                return None;
+            }
+            (false, true, b)
+        } else {
+            return None;
+        };
+
+        let source_fd = if fd_digits.is_empty() {
+            if is_input { 0 } else { 1 }
+        } else {
+            fd_digits.parse().ok()?
+        };
+
+        if let Some(dup_target) = body.strip_prefix('&') {
+            let target_fd: u32 = dup_target.parse().ok()?;
+            return Some(vec![Redirect::dup_fd(source_fd, target_fd)]);
+        }
+
+        let path = Self::take_target(body, rest)?;
+        Some(vec![Redirect::to_file(source_fd, path, is_append, is_input)])
+    }
+
+    /// A redirection's filename is either fused onto the operator
+    /// (`>out.txt`) or, when `body` is empty, the next whitespace-separated
+    /// word (`> out.txt`).
+    fn take_target(body: &str, rest: &mut Peekable<IntoIter<String>>) -> Option<String> {
+        if !body.is_empty() {
+            Some(body.to_string())
+        } else {
+            rest.next()
+        }
     }
 
     pub fn RegisterCmdProcessors(&mut self, processors: CommandProcessorMap) {
         self.processors = processors;
     }
+
+    /// Runs `commands` as a single `|`-chained pipeline against this
+    /// producer's registered processors, falling back to spawning an
+    /// external process for any stage whose name isn't registered. See
+    /// `pipeline::execute_pipeline` for the piping semantics. Records the
+    /// resulting exit code into the shared `Environment` under
+    /// `EXIT_STATUS_KEY` so a subsequent `$?` expansion reflects it.
+    ///
+    /// When the last stage is marked `Background` (a trailing `&`), the
+    /// pipeline is handed to `pipeline::spawn_pipeline_background` instead:
+    /// it registers a job in this producer's `JobManager` and returns
+    /// immediately with a success status, the same convention a real shell
+    /// uses for `cmd &` — the caller never blocks on it.
+    pub fn ExecutePipeline(&self, commands: &[Command]) -> Result<ExitStatus, Box<dyn Error>> {
+        if commands.last().is_some_and(|c| c.Background) {
+            return self.execute_background(commands);
+        }
+
+        let status =
+            pipeline::execute_pipeline(commands, &self.processors, &self.environment.borrow())?;
+        self.environment
+            .borrow_mut()
+            .set(EXIT_STATUS_KEY, status.code().to_string());
+        Ok(status)
+    }
+
+    /// Spawns `commands` as a background pipeline and prints the
+    /// conventional `[id] pid` job-start line. Every stage is run as a real
+    /// external process (see `pipeline::spawn_pipeline_background`) since a
+    /// builtin `CommandProcessor` has no child process to track a PID for.
+    fn execute_background(&self, commands: &[Command]) -> Result<ExitStatus, Box<dyn Error>> {
+        let command_line = Self::format_command_line(commands);
+        let id = pipeline::spawn_pipeline_background(
+            commands,
+            &self.environment.borrow(),
+            &mut self.job_manager.borrow_mut(),
+            command_line,
+        )?;
+        let pid = self
+            .job_manager
+            .borrow()
+            .jobs()
+            .iter()
+            .find(|job| job.id == id)
+            .and_then(|job| job.pids.last().copied())
+            .unwrap_or(0);
+        println!("[{id}] {pid}");
+        Ok(ExitStatus::success())
+    }
+
+    /// Rebuilds a pipeline's source text (`cmd1 arg | cmd2 arg &`) for the
+    /// `jobs` listing, since `spawn_pipeline_background` only keeps the
+    /// parsed `Command`s, not the original line.
+    fn format_command_line(commands: &[Command]) -> String {
+        let stages: Vec<String> = commands
+            .iter()
+            .map(|c| {
+                let mut words = vec![c.Name.clone()];
+                words.extend(c.Args.iter().cloned());
+                words.join(" ")
+            })
+            .collect();
+        format!("{} &", stages.join(" | "))
+    }
 }
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+use std::env;
+
+/// The variable name the last pipeline's exit code is stored under, read
+/// by `$?` expansion the same way any other variable is.
+pub const EXIT_STATUS_KEY: &str = "?";
+
+/// The shell's own view of its variables, kept separate from the real
+/// process environment until explicitly propagated into a spawned child —
+/// `export`/`unset` mutate this, and `run_external` feeds exactly what's
+/// in here to every external process via `env_clear`/`envs`, so a variable
+/// the shell doesn't know about never leaks into a child by accident.
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    vars: HashMap<String, String>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self {
+            vars: HashMap::new(),
+        }
+    }
+
+    pub fn capture_current() -> Self {
+        Self {
+            vars: env::vars().collect(),
+        }
+    }
+
+    pub fn with_vars(vars: HashMap<String, String>) -> Self {
+        Self { vars }
+    }
+
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.vars.insert(key.into(), value.into());
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.vars.get(key).map(|s| s.as_str())
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.vars.remove(key);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.vars.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
@@ -0,0 +1,47 @@
+use std::process::ExitStatus as ProcessExitStatus;
+
+/// A command's outcome, independent of whether it ran as a builtin
+/// `CommandProcessor` or an external `std::process::Command` — both map
+/// onto this so callers have one type to branch on regardless of which
+/// path produced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitStatus {
+    code: i32,
+}
+
+impl ExitStatus {
+    pub fn success() -> Self {
+        Self { code: 0 }
+    }
+
+    pub fn from_code(code: i32) -> Self {
+        Self { code }
+    }
+
+    /// Maps a real child's outcome onto this type, following the
+    /// conventional `128 + signum` encoding for a process killed by a
+    /// signal (e.g. SIGINT → 130) rather than collapsing it to a plain
+    /// failure code.
+    pub fn from_process(status: ProcessExitStatus) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = status.signal() {
+                return Self {
+                    code: 128 + signal,
+                };
+            }
+        }
+        Self {
+            code: status.code().unwrap_or(1),
+        }
+    }
+
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.code == 0
+    }
+}
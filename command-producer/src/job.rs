@@ -0,0 +1,20 @@
+/// Where a backgrounded pipeline currently stands. `Stopped` is part of the
+/// vocabulary (mirroring a real job table) but nothing in this crate
+/// transitions a job into it yet — there's no signal-based suspend here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Running,
+    Stopped,
+    Done(i32),
+}
+
+/// One entry in a `JobManager`'s table: the job id printed as `[id]`, the
+/// PIDs of every stage in its pipeline, the original command line (for the
+/// `jobs` listing), and its current `JobStatus`.
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: u32,
+    pub pids: Vec<u32>,
+    pub command_line: String,
+    pub status: JobStatus,
+}
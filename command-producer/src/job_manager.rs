@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::process::Child;
+
+use crate::job::{Job, JobStatus};
+
+/// Tracks pipelines launched in the background (a line ending in `&`). The
+/// plain `Job` records (`jobs()`) are what a `jobs` builtin prints; the live
+/// `Child` handles are kept alongside them so `reap_finished`/`foreground`
+/// can actually wait on the processes a job's PIDs refer to.
+pub struct JobManager {
+    jobs: Vec<Job>,
+    children: HashMap<u32, Vec<Child>>,
+    next_id: u32,
+}
+
+impl Default for JobManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self {
+            jobs: Vec::new(),
+            children: HashMap::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Registers a freshly spawned background pipeline and returns its job
+    /// id. `children` is every stage's `Child`, in pipeline order.
+    pub fn register(&mut self, command_line: String, children: Vec<Child>) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let pids = children.iter().map(|c| c.id()).collect();
+        self.jobs.push(Job {
+            id,
+            pids,
+            command_line,
+            status: JobStatus::Running,
+        });
+        self.children.insert(id, children);
+        id
+    }
+
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    /// Polls every running job's children with a non-blocking `try_wait`,
+    /// promoting a job to `Done` once all of its stages have exited. Call
+    /// this between prompts so the `jobs` listing reflects reality without
+    /// the user ever having to run `wait`.
+    pub fn reap_finished(&mut self) {
+        for job in &mut self.jobs {
+            if job.status != JobStatus::Running {
+                continue;
+            }
+            let Some(children) = self.children.get_mut(&job.id) else {
+                continue;
+            };
+            let statuses: Vec<Option<std::process::ExitStatus>> = children
+                .iter_mut()
+                .map(|c| c.try_wait().ok().flatten())
+                .collect();
+            if statuses.iter().all(Option::is_some) {
+                let code = statuses
+                    .last()
+                    .and_then(|s| *s)
+                    .and_then(|s| s.code())
+                    .unwrap_or(0);
+                job.status = JobStatus::Done(code);
+            }
+        }
+    }
+
+    /// Brings job `id` to the foreground: blocks on every one of its
+    /// children and reports the last stage's exit code, matching the shell
+    /// convention that a pipeline's status is its last stage's status.
+    /// Returns `None` if `id` isn't a known job.
+    pub fn foreground(&mut self, id: u32) -> Option<i32> {
+        let children = self.children.get_mut(&id)?;
+        let mut code = 0;
+        for child in children.iter_mut() {
+            if let Ok(status) = child.wait() {
+                code = status.code().unwrap_or(0);
+            }
+        }
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+            job.status = JobStatus::Done(code);
+        }
+        Some(code)
+    }
+}
@@ -1,9 +1,22 @@
+pub mod builtins;
 pub mod command;
 pub mod command_name;
 pub mod command_processor;
 pub mod command_producer;
+pub mod environment;
+pub mod exit_status;
+pub mod job;
+pub mod job_manager;
+pub mod pipeline;
+pub mod redirect;
 
 pub use command::Command;
 pub use command_name::CommandName;
 pub use command_processor::{CommandProcessor, CommandProcessorMap};
 pub use command_producer::CommandProducer;
+pub use environment::Environment;
+pub use exit_status::ExitStatus;
+pub use job::{Job, JobStatus};
+pub use job_manager::JobManager;
+pub use pipeline::{execute_pipeline, spawn_pipeline_background};
+pub use redirect::{Redirect, RedirectTarget};
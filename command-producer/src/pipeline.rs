@@ -0,0 +1,236 @@
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Cursor, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command as OsCommand, Stdio};
+
+use crate::command::Command;
+use crate::command_name::CommandName;
+use crate::command_processor::CommandProcessorMap;
+use crate::environment::Environment;
+use crate::exit_status::ExitStatus;
+use crate::job_manager::JobManager;
+use crate::redirect::RedirectTarget;
+
+// No `libc` dependency is available in this crate, so the one syscall
+// `run_external` needs for fd redirection — `dup2`, to land an opened file
+// (or another fd) onto its target fd — is declared directly.
+extern "C" {
+    fn dup2(oldfd: i32, newfd: i32) -> i32;
+}
+
+/// A `Redirect`, resolved to whatever `dup2` needs to replay it: either a
+/// raw fd already open in this process (for a `File` target, opened ahead
+/// of `fork` since the child may not safely allocate between `fork` and
+/// `exec`), or another fd number to duplicate in place (for the `N>&M`
+/// form).
+enum PreparedRedirect {
+    File { source_fd: u32, file: File },
+    Dup { source_fd: u32, target_fd: u32 },
+}
+
+/// Opens every `File`-target redirect ahead of spawning, preserving
+/// `command.Redirects`'s order so `prepare_redirects`'s caller can replay
+/// them with plain sequential `dup2` calls and get the same left-to-right
+/// semantics a real shell gives `>out 2>&1` vs `2>&1 >out`.
+fn prepare_redirects(command: &Command) -> io::Result<Vec<PreparedRedirect>> {
+    command
+        .Redirects
+        .iter()
+        .map(|redirect| match &redirect.target {
+            RedirectTarget::File(path) => {
+                let file = if redirect.read {
+                    OpenOptions::new().read(true).open(path)?
+                } else if redirect.append {
+                    OpenOptions::new().create(true).append(true).open(path)?
+                } else {
+                    OpenOptions::new()
+                        .write(true)
+                        .create(true)
+                        .truncate(true)
+                        .open(path)?
+                };
+                Ok(PreparedRedirect::File {
+                    source_fd: redirect.source_fd,
+                    file,
+                })
+            }
+            RedirectTarget::Fd(target_fd) => Ok(PreparedRedirect::Dup {
+                source_fd: redirect.source_fd,
+                target_fd: *target_fd,
+            }),
+        })
+        .collect()
+}
+
+/// Runs `commands` as a single `|`-chained pipeline: each stage's captured
+/// stdout becomes an in-memory buffer fed as the next stage's stdin, the
+/// same wiring `Stdio::piped()` gives two external processes, but done in
+/// memory so a builtin `CommandProcessor` and a spawned binary can sit on
+/// either side of the same pipe. Every stage's stderr is written to the
+/// real stderr; only the final stage's stdout is written to the real
+/// stdout, and every interior stage's stdout is consumed entirely as the
+/// next stage's input. Any external stage gets
+/// exactly `environment`'s variables, not whatever the shell process itself
+/// happens to have. Returns the last stage's `ExitStatus`.
+pub fn execute_pipeline(
+    commands: &[Command],
+    processors: &CommandProcessorMap,
+    environment: &Environment,
+) -> Result<ExitStatus, Box<dyn Error>> {
+    let mut input: Vec<u8> = Vec::new();
+    let mut status = ExitStatus::success();
+
+    for (i, command) in commands.iter().enumerate() {
+        let is_last = i == commands.len() - 1;
+        let mut stdout_buf: Vec<u8> = Vec::new();
+        let mut stderr_buf: Vec<u8> = Vec::new();
+        // A heredoc/here-string body takes the place of whatever the
+        // previous stage would otherwise have piped in.
+        let mut reader = match &command.Stdin {
+            Some(text) => Cursor::new(text.clone().into_bytes()),
+            None => Cursor::new(input),
+        };
+
+        status = match processors.get(&CommandName::from(command.Name.as_str())) {
+            Some(processor) => {
+                processor.process(command, &mut reader, &mut stdout_buf, &mut stderr_buf)?
+            }
+            None => run_external(
+                command,
+                environment,
+                &mut reader,
+                &mut stdout_buf,
+                &mut stderr_buf,
+            )?,
+        };
+
+        std::io::stderr().write_all(&stderr_buf)?;
+        if is_last {
+            std::io::stdout().write_all(&stdout_buf)?;
+        }
+
+        input = stdout_buf;
+    }
+
+    Ok(status)
+}
+
+/// Spawns `command` as a real OS process, piping `stdin` in and capturing
+/// its stdout/stderr — the fallback `execute_pipeline` takes for any stage
+/// whose name isn't registered in the `CommandProcessorMap`. The child's
+/// environment is cleared and reseeded from `environment` rather than
+/// inherited from this process, so a variable the shell never `export`ed
+/// stays invisible to it.
+fn run_external(
+    command: &Command,
+    environment: &Environment,
+    stdin: &mut dyn Read,
+    stdout: &mut dyn Write,
+    stderr: &mut dyn Write,
+) -> Result<ExitStatus, Box<dyn Error>> {
+    let prepared = prepare_redirects(command)?;
+    let stdin_redirected = command.Redirects.iter().any(|r| r.source_fd == 0);
+
+    let mut os_command = OsCommand::new(&command.Name);
+    os_command
+        .args(&command.Args)
+        .env_clear()
+        .envs(environment.iter())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    if !prepared.is_empty() {
+        // Safety: every `File` in `prepared` was opened above, before
+        // `fork`, so the closure only calls `dup2` on fds that already
+        // exist — no allocation or other fork-unsafe work happens here.
+        unsafe {
+            os_command.pre_exec(move || {
+                for redirect in &prepared {
+                    let result = match redirect {
+                        PreparedRedirect::File { source_fd, file } => {
+                            dup2(file.as_raw_fd(), *source_fd as i32)
+                        }
+                        PreparedRedirect::Dup {
+                            source_fd,
+                            target_fd,
+                        } => dup2(*target_fd as i32, *source_fd as i32),
+                    };
+                    if result < 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+
+    let mut child = os_command.spawn()?;
+
+    if stdin_redirected {
+        // fd 0 was replayed onto a real file above; the piped stdin this
+        // process also set up never gets read by the child, so dropping it
+        // (instead of writing into it) avoids blocking on a pipe nobody
+        // drains.
+        drop(child.stdin.take());
+    } else {
+        let mut input_buf = Vec::new();
+        stdin.read_to_end(&mut input_buf)?;
+        child
+            .stdin
+            .take()
+            .expect("stdin was piped")
+            .write_all(&input_buf)?;
+    }
+
+    let output = child.wait_with_output()?;
+    stdout.write_all(&output.stdout)?;
+    stderr.write_all(&output.stderr)?;
+
+    Ok(ExitStatus::from_process(output.status))
+}
+
+/// Launches `commands` as a background pipeline (an input line ending in
+/// `&`) and registers it in `job_manager`, returning its new job id.
+///
+/// Unlike `execute_pipeline`, every stage here is an external process
+/// connected by a real OS pipe (`Stdio::piped()` feeding directly into the
+/// next stage's `stdin`) so the whole chain runs concurrently without the
+/// shell blocking on it — a builtin `CommandProcessor` has no child process
+/// to hand back a PID for, so backgrounding only supports external stages;
+/// a stage named after a registered builtin is still spawned as an external
+/// program of that name.
+pub fn spawn_pipeline_background(
+    commands: &[Command],
+    environment: &Environment,
+    job_manager: &mut JobManager,
+    command_line: String,
+) -> Result<u32, Box<dyn Error>> {
+    let mut children: Vec<Child> = Vec::with_capacity(commands.len());
+    let mut previous_stdout: Option<std::process::ChildStdout> = None;
+
+    for (i, command) in commands.iter().enumerate() {
+        let is_last = i == commands.len() - 1;
+        let mut os_command = OsCommand::new(&command.Name);
+        os_command.args(&command.Args).env_clear().envs(environment.iter());
+
+        os_command.stdin(match previous_stdout.take() {
+            Some(stdout) => Stdio::from(stdout),
+            None => Stdio::null(),
+        });
+        os_command.stdout(if is_last {
+            Stdio::null()
+        } else {
+            Stdio::piped()
+        });
+        os_command.stderr(Stdio::inherit());
+
+        let mut child = os_command.spawn()?;
+        previous_stdout = child.stdout.take();
+        children.push(child);
+    }
+
+    Ok(job_manager.register(command_line, children))
+}
@@ -0,0 +1,43 @@
+/// A single fd redirection parsed from a command line, kept in the order it
+/// was written so replaying the list reproduces shell semantics where later
+/// redirects on the same fd win: `>out 2>&1` merges stderr into the file,
+/// while `2>&1 >out` leaves stderr pointing at whatever fd 1 was *before*
+/// the `>out` took effect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    pub source_fd: u32,
+    pub target: RedirectTarget,
+    pub append: bool,
+    /// Whether `target` (when it's a `File`) is opened for reading (`N<`)
+    /// rather than writing — meaningless for a `Fd` duplication target,
+    /// whose direction just follows whatever the duplicated fd already is.
+    pub read: bool,
+}
+
+/// Where a `Redirect` points: a path to open, or another fd to duplicate
+/// (the `N>&M` / `2>&1` form).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedirectTarget {
+    File(String),
+    Fd(u32),
+}
+
+impl Redirect {
+    pub fn to_file(source_fd: u32, path: impl Into<String>, append: bool, read: bool) -> Self {
+        Self {
+            source_fd,
+            target: RedirectTarget::File(path.into()),
+            append,
+            read,
+        }
+    }
+
+    pub fn dup_fd(source_fd: u32, target_fd: u32) -> Self {
+        Self {
+            source_fd,
+            target: RedirectTarget::Fd(target_fd),
+            append: false,
+            read: false,
+        }
+    }
+}
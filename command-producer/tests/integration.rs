@@ -11,13 +11,232 @@ fn test_produce_commands() {
 
     assert_eq!(commands[0].Name, "ls");
     assert_eq!(commands[0].Args, vec!["-la".to_string()]);
-    assert_eq!(commands[0].Stdin, None);
-    assert_eq!(commands[0].Stdout, None);
+    assert!(commands[0].Redirects.is_empty());
 
     assert_eq!(commands[1].Name, "echo");
     assert_eq!(commands[1].Args, vec!["hello".to_string()]);
+    assert!(commands[1].Redirects.is_empty());
+}
+
+#[test]
+fn test_produce_commands_parses_redirections() {
+    use command_producer::{Redirect, RedirectTarget};
+
+    let mut producer = CommandProducer::new();
+    let input = vec![
+        "echo hi > out.txt".to_string(),
+        "grep foo < in.txt >> out.log".to_string(),
+        "cmd 2>&1".to_string(),
+        "cmd &>both.log".to_string(),
+    ];
+
+    let commands = producer.ProduceCommands(input);
+
+    assert_eq!(
+        commands[0].Redirects,
+        vec![Redirect::to_file(1, "out.txt", false, false)]
+    );
+
+    assert_eq!(
+        commands[1].Redirects,
+        vec![
+            Redirect::to_file(0, "in.txt", false, true),
+            Redirect::to_file(1, "out.log", true, false),
+        ]
+    );
+
+    assert_eq!(commands[2].Redirects, vec![Redirect::dup_fd(2, 1)]);
+
+    assert_eq!(
+        commands[3].Redirects,
+        vec![
+            Redirect::to_file(1, "both.log", false, false),
+            Redirect::dup_fd(2, 1),
+        ]
+    );
+    match &commands[3].Redirects[0].target {
+        RedirectTarget::File(path) => assert_eq!(path, "both.log"),
+        other => panic!("expected a File target, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_produce_commands_collects_heredoc_body_from_following_lines() {
+    let mut producer = CommandProducer::new();
+    let input = vec![
+        "cat <<EOF".to_string(),
+        "line one".to_string(),
+        "line two".to_string(),
+        "EOF".to_string(),
+        "echo after".to_string(),
+    ];
+
+    let commands = producer.ProduceCommands(input);
+
+    assert_eq!(commands.len(), 2);
+    assert_eq!(commands[0].Name, "cat");
+    assert_eq!(
+        commands[0].Stdin.as_deref(),
+        Some("line one\nline two\n")
+    );
+
+    assert_eq!(commands[1].Name, "echo");
     assert_eq!(commands[1].Stdin, None);
-    assert_eq!(commands[1].Stdout, None);
+}
+
+#[test]
+fn test_produce_commands_strips_quotes_from_heredoc_delimiter() {
+    let mut producer = CommandProducer::new();
+    let input = vec![
+        "cat <<'EOF'".to_string(),
+        "literal $HOME".to_string(),
+        "EOF".to_string(),
+    ];
+
+    let commands = producer.ProduceCommands(input);
+
+    assert_eq!(commands[0].Stdin.as_deref(), Some("literal $HOME\n"));
+}
+
+#[test]
+fn test_produce_commands_parses_here_string() {
+    let mut producer = CommandProducer::new();
+    let input = vec!["cat <<<hello".to_string()];
+
+    let commands = producer.ProduceCommands(input);
+
+    assert_eq!(commands[0].Stdin.as_deref(), Some("hello\n"));
+}
+
+#[test]
+fn test_produce_commands_parses_trailing_ampersand_as_background() {
+    let mut producer = CommandProducer::new();
+    let input = vec![
+        "sleep 1 &".to_string(),
+        "echo not-background".to_string(),
+    ];
+
+    let commands = producer.ProduceCommands(input);
+
+    assert!(commands[0].Background);
+    assert_eq!(commands[0].Args, vec!["1".to_string()]);
+    assert!(!commands[1].Background);
+}
+
+#[test]
+fn test_execute_pipeline_backgrounds_a_trailing_ampersand_pipeline() {
+    let mut producer = CommandProducer::new();
+    let input = vec!["/usr/bin/true &".to_string()];
+    let commands = producer.ProduceCommands(input);
+    assert!(commands[0].Background);
+
+    let status = producer
+        .ExecutePipeline(&commands)
+        .expect("backgrounding the pipeline failed");
+    assert!(status.is_success());
+    assert_eq!(producer.job_manager().borrow().jobs().len(), 1);
+}
+
+#[test]
+fn test_jobs_fg_bg_wait_builtins_track_a_background_job() {
+    use std::time::{Duration, Instant};
+
+    use command_producer::builtins::{BgBuiltin, FgBuiltin, JobsBuiltin, WaitBuiltin};
+    use command_producer::{Command, CommandProcessor, Environment, JobManager};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let job_manager = Rc::new(RefCell::new(JobManager::new()));
+    let commands = vec![Command {
+        Name: "/usr/bin/true".to_string(),
+        Args: vec![],
+        Redirects: vec![],
+        Stdin: None,
+        Background: true,
+    }];
+    let id = command_producer::spawn_pipeline_background(
+        &commands,
+        &Environment::new(),
+        &mut job_manager.borrow_mut(),
+        "/usr/bin/true &".to_string(),
+    )
+    .expect("failed to spawn background pipeline");
+
+    let jobs = JobsBuiltin::new(job_manager.clone());
+    let mut listing = Vec::new();
+    jobs.process(
+        &Command {
+            Name: "jobs".to_string(),
+            Args: vec![],
+            Redirects: vec![],
+            Stdin: None,
+            Background: false,
+        },
+        &mut std::io::empty(),
+        &mut listing,
+        &mut std::io::sink(),
+    )
+    .expect("jobs failed");
+    assert!(String::from_utf8(listing).unwrap().contains(&format!("[{id}]")));
+
+    let bg = BgBuiltin::new(job_manager.clone());
+    let mut bg_out = Vec::new();
+    bg.process(
+        &Command {
+            Name: "bg".to_string(),
+            Args: vec![format!("%{id}")],
+            Redirects: vec![],
+            Stdin: None,
+            Background: false,
+        },
+        &mut std::io::empty(),
+        &mut bg_out,
+        &mut std::io::sink(),
+    )
+    .expect("bg failed");
+    assert!(String::from_utf8(bg_out).unwrap().contains(&format!("[{id}]")));
+
+    // `/usr/bin/true` exits almost instantly; give it a moment before the
+    // blocking builtins below so the deadline below isn't needed to mask a
+    // slow scheduler.
+    let deadline = Instant::now() + Duration::from_millis(200);
+    while Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let wait = WaitBuiltin::new(job_manager.clone());
+    let status = wait
+        .process(
+            &Command {
+                Name: "wait".to_string(),
+                Args: vec![format!("%{id}")],
+                Redirects: vec![],
+                Stdin: None,
+                Background: false,
+            },
+            &mut std::io::empty(),
+            &mut std::io::sink(),
+            &mut std::io::sink(),
+        )
+        .expect("wait failed");
+    assert!(status.is_success());
+
+    let fg = FgBuiltin::new(job_manager.clone());
+    let status = fg
+        .process(
+            &Command {
+                Name: "fg".to_string(),
+                Args: vec![format!("%{id}")],
+                Redirects: vec![],
+                Stdin: None,
+                Background: false,
+            },
+            &mut std::io::empty(),
+            &mut std::io::sink(),
+            &mut std::io::sink(),
+        )
+        .expect("fg failed");
+    assert!(status.is_success());
 }
 
 #[test]
@@ -30,3 +249,295 @@ fn test_register_processors() {
 
     producer.RegisterCmdProcessors(processors);
 }
+
+/// A builtin that writes each arg, uppercased, on its own line — just
+/// enough behavior to prove a `CommandProcessor` reads `stdin` and writes
+/// `stdout` through `execute_pipeline` rather than touching the real
+/// process streams directly.
+struct UpperEcho;
+
+impl command_producer::CommandProcessor for UpperEcho {
+    fn process(
+        &self,
+        command: &command_producer::Command,
+        _stdin: &mut dyn std::io::Read,
+        stdout: &mut dyn std::io::Write,
+        _stderr: &mut dyn std::io::Write,
+    ) -> Result<command_producer::ExitStatus, Box<dyn std::error::Error>> {
+        for arg in &command.Args {
+            writeln!(stdout, "{}", arg.to_uppercase())?;
+        }
+        Ok(command_producer::ExitStatus::success())
+    }
+}
+
+/// A builtin that copies stdin to stdout verbatim, mirroring `cat` with no
+/// args, and stashes a copy of whatever it read into a shared buffer — so
+/// a test can assert on the bytes this stage actually received as its
+/// piped stdin, not just on the pipeline's exit status.
+struct RecordingCat {
+    received: std::rc::Rc<std::cell::RefCell<Vec<u8>>>,
+}
+
+impl RecordingCat {
+    fn new(received: std::rc::Rc<std::cell::RefCell<Vec<u8>>>) -> Self {
+        Self { received }
+    }
+}
+
+impl command_producer::CommandProcessor for RecordingCat {
+    fn process(
+        &self,
+        _command: &command_producer::Command,
+        stdin: &mut dyn std::io::Read,
+        stdout: &mut dyn std::io::Write,
+        _stderr: &mut dyn std::io::Write,
+    ) -> Result<command_producer::ExitStatus, Box<dyn std::error::Error>> {
+        let mut buf = Vec::new();
+        stdin.read_to_end(&mut buf)?;
+        self.received.borrow_mut().extend_from_slice(&buf);
+        stdout.write_all(&buf)?;
+        Ok(command_producer::ExitStatus::success())
+    }
+}
+
+#[test]
+fn test_execute_pipeline_chains_builtin_stdout_into_next_stdin() {
+    use command_producer::{Command, CommandName, CommandProcessorMap};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let mut processors: CommandProcessorMap = CommandProcessorMap::new();
+    processors.insert(CommandName::from("upper"), Box::new(UpperEcho));
+
+    // `cat`'s own process() only proves it forwards bytes; recording what
+    // actually reaches the final stage's stdin is what proves `upper`'s
+    // stdout was the thing piped in, not just that the pipeline succeeded.
+    let received = Rc::new(RefCell::new(Vec::new()));
+    processors.insert(
+        CommandName::from("cat"),
+        Box::new(RecordingCat::new(received.clone())),
+    );
+
+    let mut producer = CommandProducer::new();
+    producer.RegisterCmdProcessors(processors);
+
+    let commands = vec![
+        Command {
+            Name: "upper".to_string(),
+            Args: vec!["hi".to_string()],
+            Redirects: vec![],
+            Stdin: None,
+            Background: false,
+        },
+        Command {
+            Name: "cat".to_string(),
+            Args: vec![],
+            Redirects: vec![],
+            Stdin: None,
+            Background: false,
+        },
+    ];
+
+    let status = producer
+        .ExecutePipeline(&commands)
+        .expect("pipeline execution failed");
+    assert!(status.is_success());
+    assert_eq!(String::from_utf8(received.borrow().clone()).unwrap(), "HI\n");
+}
+
+#[test]
+fn test_background_pipeline_is_tracked_as_a_job() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+
+    use command_producer::{Command, Environment, JobManager};
+
+    let commands = vec![Command {
+        Name: "/usr/bin/true".to_string(),
+        Args: vec![],
+        Redirects: vec![],
+        Stdin: None,
+        Background: false,
+    }];
+
+    let job_manager = Rc::new(RefCell::new(JobManager::new()));
+    let id = command_producer::spawn_pipeline_background(
+        &commands,
+        &Environment::new(),
+        &mut job_manager.borrow_mut(),
+        "true &".to_string(),
+    )
+    .expect("failed to spawn background pipeline");
+
+    assert_eq!(job_manager.borrow().jobs().len(), 1);
+    assert_eq!(job_manager.borrow().jobs()[0].id, id);
+
+    // `true` exits almost instantly; give the child a moment to finish so
+    // `reap_finished` has something to promote out of `Running`.
+    let deadline = Instant::now() + Duration::from_secs(2);
+    loop {
+        job_manager.borrow_mut().reap_finished();
+        if !matches!(
+            job_manager.borrow().jobs()[0].status,
+            command_producer::JobStatus::Running
+        ) || Instant::now() > deadline
+        {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(
+        job_manager.borrow().jobs()[0].status,
+        command_producer::JobStatus::Done(0)
+    );
+}
+
+#[test]
+fn test_export_and_unset_builtins_mutate_shared_environment() {
+    use command_producer::builtins::{ExportBuiltin, UnsetBuiltin};
+    use command_producer::{Command, CommandProcessor};
+
+    let producer = CommandProducer::new();
+    let environment = producer.environment();
+
+    let export = ExportBuiltin::new(environment.clone());
+    let command = Command {
+        Name: "export".to_string(),
+        Args: vec!["FOO=bar".to_string()],
+        Redirects: vec![],
+        Stdin: None,
+        Background: false,
+    };
+    export
+        .process(
+            &command,
+            &mut std::io::empty(),
+            &mut std::io::sink(),
+            &mut std::io::sink(),
+        )
+        .expect("export failed");
+    assert_eq!(environment.borrow().get("FOO"), Some("bar"));
+
+    let unset = UnsetBuiltin::new(environment.clone());
+    let command = Command {
+        Name: "unset".to_string(),
+        Args: vec!["FOO".to_string()],
+        Redirects: vec![],
+        Stdin: None,
+        Background: false,
+    };
+    unset
+        .process(
+            &command,
+            &mut std::io::empty(),
+            &mut std::io::sink(),
+            &mut std::io::sink(),
+        )
+        .expect("unset failed");
+    assert_eq!(environment.borrow().get("FOO"), None);
+}
+
+#[test]
+fn test_execute_pipeline_records_exit_status_for_dollar_question() {
+    use command_producer::{Command, CommandName, CommandProcessorMap};
+
+    struct Failing;
+    impl command_producer::CommandProcessor for Failing {
+        fn process(
+            &self,
+            _command: &Command,
+            _stdin: &mut dyn std::io::Read,
+            _stdout: &mut dyn std::io::Write,
+            _stderr: &mut dyn std::io::Write,
+        ) -> Result<command_producer::ExitStatus, Box<dyn std::error::Error>> {
+            Ok(command_producer::ExitStatus::from_code(7))
+        }
+    }
+
+    let mut processors: CommandProcessorMap = CommandProcessorMap::new();
+    processors.insert(CommandName::from("fail"), Box::new(Failing));
+
+    let mut producer = CommandProducer::new();
+    producer.RegisterCmdProcessors(processors);
+
+    let commands = vec![Command {
+        Name: "fail".to_string(),
+        Args: vec![],
+        Redirects: vec![],
+        Stdin: None,
+        Background: false,
+    }];
+
+    producer
+        .ExecutePipeline(&commands)
+        .expect("pipeline execution failed");
+
+    assert_eq!(
+        producer.environment().borrow().get("?"),
+        Some("7")
+    );
+}
+
+#[test]
+fn test_redirect_order_determines_whether_stderr_merges_into_the_file() {
+    use command_producer::{Command, CommandProcessorMap, Redirect};
+    use std::fs;
+
+    let sh_args = |script: &str| {
+        vec!["-c".to_string(), script.to_string()]
+    };
+
+    // `>out 2>&1`: stdout is redirected to the file first, so the later
+    // `2>&1` duplicates *that* file onto fd 2 as well — both streams land
+    // in the file.
+    let merged_path = std::env::temp_dir().join("cp_redirect_merged.txt");
+    let _ = fs::remove_file(&merged_path);
+    let commands = vec![Command {
+        Name: "sh".to_string(),
+        Args: sh_args("echo out; echo err 1>&2"),
+        Redirects: vec![
+            Redirect::to_file(1, merged_path.to_string_lossy().to_string(), false, false),
+            Redirect::dup_fd(2, 1),
+        ],
+        Stdin: None,
+        Background: false,
+    }];
+    let producer = CommandProducer::new();
+    producer
+        .ExecutePipeline(&commands)
+        .expect("pipeline execution failed");
+    let merged_contents = fs::read_to_string(&merged_path).expect("merged file should exist");
+    assert!(merged_contents.contains("out"));
+    assert!(merged_contents.contains("err"));
+    let _ = fs::remove_file(&merged_path);
+
+    // `2>&1 >out`: fd 2 is duplicated from fd 1 *before* fd 1 is
+    // redirected, so fd 2 goes to the original stdout stream rather than
+    // the file, and only stdout ends up in the file.
+    let stdout_only_path = std::env::temp_dir().join("cp_redirect_stdout_only.txt");
+    let _ = fs::remove_file(&stdout_only_path);
+    let commands = vec![Command {
+        Name: "sh".to_string(),
+        Args: sh_args("echo out; echo err 1>&2"),
+        Redirects: vec![
+            Redirect::dup_fd(2, 1),
+            Redirect::to_file(1, stdout_only_path.to_string_lossy().to_string(), false, false),
+        ],
+        Stdin: None,
+        Background: false,
+    }];
+    let processors: CommandProcessorMap = CommandProcessorMap::new();
+    let mut producer = CommandProducer::new();
+    producer.RegisterCmdProcessors(processors);
+    producer
+        .ExecutePipeline(&commands)
+        .expect("pipeline execution failed");
+    let stdout_only_contents =
+        fs::read_to_string(&stdout_only_path).expect("stdout-only file should exist");
+    assert!(stdout_only_contents.contains("out"));
+    assert!(!stdout_only_contents.contains("err"));
+    let _ = fs::remove_file(&stdout_only_path);
+}
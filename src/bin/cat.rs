@@ -3,37 +3,166 @@ use std::fs;
 use std::io::{self, Read, Write};
 use std::process;
 
+/// Parsed command-line flags, mirroring the coreutils subset of `cat`'s options.
+#[derive(Default)]
+struct Flags {
+    number_all: bool,
+    number_nonblank: bool,
+    squeeze_blank: bool,
+    show_ends: bool,
+    show_nonprinting: bool,
+}
+
+/// Splits `args` into flags and file operands, stopping flag parsing at `--`.
+/// A bare `-` is kept as a file operand meaning stdin.
+fn parse_args(args: &[String]) -> (Flags, Vec<&String>) {
+    let mut flags = Flags::default();
+    let mut files = Vec::new();
+    let mut no_more_flags = false;
+
+    for arg in args {
+        if no_more_flags || arg == "-" || !arg.starts_with('-') {
+            files.push(arg);
+            continue;
+        }
+        if arg == "--" {
+            no_more_flags = true;
+            continue;
+        }
+        match arg.as_str() {
+            "-n" | "--number" => flags.number_all = true,
+            "-b" => flags.number_nonblank = true,
+            "-s" => flags.squeeze_blank = true,
+            "-E" => flags.show_ends = true,
+            "-A" => flags.show_nonprinting = true,
+            _ => {
+                eprintln!("cat: invalid option -- '{}'", arg);
+                process::exit(1);
+            }
+        }
+    }
+
+    (flags, files)
+}
+
+/// Renders non-printing characters the way `cat -A` does: `^`-prefixed
+/// control codes and `M-`-prefixed characters in U+0080..=U+00FF (by code
+/// point, not by UTF-8 byte); characters above U+00FF pass through
+/// unchanged. The trailing `$` per line that `-A` also implies (subsuming
+/// `-E`) is written by `write_content`, not here.
+fn show_nonprinting(line: &str) -> String {
+    let mut out = String::new();
+    for ch in line.chars() {
+        let code = ch as u32;
+        if ch == '\t' {
+            out.push_str("^I");
+        } else if code < 0x20 {
+            out.push('^');
+            out.push((code as u8 + 0x40) as char);
+        } else if code == 0x7f {
+            out.push_str("^?");
+        } else if (0x80..0xa0).contains(&code) {
+            out.push_str("M-^");
+            out.push((code as u8 - 0x80 + 0x40) as char);
+        } else if code >= 0xa0 && code <= 0xff {
+            out.push_str("M-");
+            out.push((code as u8 - 0x80) as char);
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// Writes `content` applying `flags`, continuing the line-number sequence
+/// across files via `line_number` and the blank-squeeze state via `last_blank`.
+fn write_content(
+    out: &mut impl Write,
+    content: &str,
+    flags: &Flags,
+    line_number: &mut u64,
+    last_blank: &mut bool,
+) -> io::Result<()> {
+    let had_trailing_newline = content.ends_with('\n');
+    let mut lines = content.split('\n').peekable();
+    if had_trailing_newline {
+        // split('\n') yields a trailing empty element after the final newline.
+        lines.next_back();
+    }
+
+    while let Some(line) = lines.next() {
+        let is_blank = line.is_empty();
+        if flags.squeeze_blank && is_blank && *last_blank {
+            continue;
+        }
+        *last_blank = is_blank;
+
+        let numbered = flags.number_all || (flags.number_nonblank && !is_blank);
+        if numbered {
+            write!(out, "{:>6}\t", *line_number)?;
+            *line_number += 1;
+        }
+
+        let rendered = if flags.show_nonprinting {
+            show_nonprinting(line)
+        } else {
+            line.to_string()
+        };
+        out.write_all(rendered.as_bytes())?;
+
+        if flags.show_ends || flags.show_nonprinting {
+            out.write_all(b"$")?;
+        }
+        if lines.peek().is_some() || had_trailing_newline {
+            out.write_all(b"\n")?;
+        }
+    }
+    Ok(())
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
+    let (flags, files) = parse_args(&args[1..]);
 
-    // Skip program name
-    let file_args: Vec<&String> = args.iter().skip(1).collect();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut line_number: u64 = 1;
+    let mut last_blank = false;
 
-    if file_args.is_empty() {
-        // Read from stdin if no files provided
+    if files.is_empty() {
         let mut input = String::new();
         match io::stdin().read_to_string(&mut input) {
-            Ok(_) => print!("{}", input),
+            Ok(_) => write_content(&mut out, &input, &flags, &mut line_number, &mut last_blank)
+                .unwrap(),
             Err(e) => {
                 eprintln!("cat: error reading from stdin: {}", e);
                 process::exit(1);
             }
         }
     } else {
-        // Read and print each file
-        for file_path in file_args {
-            match fs::read_to_string(file_path) {
-                Ok(content) => print!("{}", content),
-                Err(e) => {
-                    eprintln!("cat: {}: {}", file_path, e);
+        for file_path in files {
+            let content = if file_path == "-" {
+                let mut input = String::new();
+                if let Err(e) = io::stdin().read_to_string(&mut input) {
+                    eprintln!("cat: error reading from stdin: {}", e);
                     process::exit(1);
                 }
-            }
+                input
+            } else {
+                match fs::read_to_string(file_path) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        eprintln!("cat: {}: {}", file_path, e);
+                        process::exit(1);
+                    }
+                }
+            };
+            write_content(&mut out, &content, &flags, &mut line_number, &mut last_blank).unwrap();
         }
     }
 
     // Ensure output is flushed
-    io::stdout().flush().unwrap();
+    out.flush().unwrap();
 }
 
 #[cfg(test)]
@@ -169,4 +298,142 @@ mod tests {
         assert_eq!(String::from_utf8_lossy(&output.stdout), input);
         assert!(output.stderr.is_empty());
     }
+
+    #[test]
+    fn test_cat_number_all_lines() {
+        let test_dir = env::temp_dir().join("cat_test_number_all");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let test_file = test_dir.join("test.txt");
+        fs::write(&test_file, "one\n\nthree\n").expect("Failed to write test file");
+
+        let output = Command::new(get_cat_binary_path())
+            .arg("-n")
+            .arg(&test_file)
+            .output()
+            .expect("Failed to execute cat command");
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "     1\tone\n     2\t\n     3\tthree\n"
+        );
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_cat_number_nonblank_lines() {
+        let test_dir = env::temp_dir().join("cat_test_number_nonblank");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let test_file = test_dir.join("test.txt");
+        fs::write(&test_file, "one\n\nthree\n").expect("Failed to write test file");
+
+        let output = Command::new(get_cat_binary_path())
+            .arg("-b")
+            .arg(&test_file)
+            .output()
+            .expect("Failed to execute cat command");
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "     1\tone\n\n     2\tthree\n"
+        );
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_cat_squeeze_blank_lines() {
+        let test_dir = env::temp_dir().join("cat_test_squeeze");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let test_file = test_dir.join("test.txt");
+        fs::write(&test_file, "one\n\n\n\ntwo\n").expect("Failed to write test file");
+
+        let output = Command::new(get_cat_binary_path())
+            .arg("-s")
+            .arg(&test_file)
+            .output()
+            .expect("Failed to execute cat command");
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "one\n\ntwo\n");
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_cat_show_ends() {
+        let test_dir = env::temp_dir().join("cat_test_show_ends");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let test_file = test_dir.join("test.txt");
+        fs::write(&test_file, "one\ntwo\n").expect("Failed to write test file");
+
+        let output = Command::new(get_cat_binary_path())
+            .arg("-E")
+            .arg(&test_file)
+            .output()
+            .expect("Failed to execute cat command");
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "one$\ntwo$\n");
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_cat_show_nonprinting() {
+        let test_dir = env::temp_dir().join("cat_test_nonprinting");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let test_file = test_dir.join("test.txt");
+        fs::write(&test_file, "a\tb\n").expect("Failed to write test file");
+
+        let output = Command::new(get_cat_binary_path())
+            .arg("-A")
+            .arg(&test_file)
+            .output()
+            .expect("Failed to execute cat command");
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "a^Ib$\n");
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_cat_numbering_continuous_across_files() {
+        let test_dir = env::temp_dir().join("cat_test_number_multi");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let file1 = test_dir.join("file1.txt");
+        let file2 = test_dir.join("file2.txt");
+        fs::write(&file1, "a\n").expect("Failed to write file1");
+        fs::write(&file2, "b\n").expect("Failed to write file2");
+
+        let output = Command::new(get_cat_binary_path())
+            .arg("-n")
+            .arg(&file1)
+            .arg(&file2)
+            .output()
+            .expect("Failed to execute cat command");
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "     1\ta\n     2\tb\n"
+        );
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
 }
@@ -4,14 +4,109 @@ use std::env;
 /// Prints all arguments separated by spaces to stdout
 fn main() {
     let args: Vec<String> = env::args().skip(1).collect();
+    let (suppress_newline, interpret_escapes, flag_count) = parse_flags(&args);
+    let text = args[flag_count..].join(" ");
 
-    if args.is_empty() {
-        println!();
+    let (text, stop) = if interpret_escapes {
+        interpret_backslash_escapes(&text)
     } else {
-        println!("{}", args.join(" "));
+        (text, false)
+    };
+
+    print!("{}", text);
+    if !suppress_newline && !stop {
+        println!();
     }
 }
 
+/// Consumes leading `-`-prefixed args that consist solely of `n`/`e`/`E`
+/// flag letters (bash's echo rule), combining them (`-nE` is `-n` + `-E`)
+/// and stopping at the first arg that isn't a pure flag word — including
+/// unrecognized ones like `--help`. Returns `(suppress_newline,
+/// interpret_escapes, number of leading args consumed as flags)`.
+fn parse_flags(args: &[String]) -> (bool, bool, usize) {
+    let mut suppress_newline = false;
+    let mut interpret_escapes = false;
+    let mut consumed = 0;
+
+    for arg in args {
+        let is_flag_word = arg.len() > 1
+            && arg.starts_with('-')
+            && arg[1..].chars().all(|c| matches!(c, 'n' | 'e' | 'E'));
+        if !is_flag_word {
+            break;
+        }
+        for c in arg[1..].chars() {
+            match c {
+                'n' => suppress_newline = true,
+                'e' => interpret_escapes = true,
+                'E' => interpret_escapes = false,
+                _ => unreachable!(),
+            }
+        }
+        consumed += 1;
+    }
+
+    (suppress_newline, interpret_escapes, consumed)
+}
+
+/// Interprets backslash escapes the way bash's `echo -e` does: `\n`, `\t`,
+/// `\r`, `\\`, `\0NNN` (up to three octal digits), `\xHH` (up to two hex
+/// digits), and `\c` which stops all further output (including the
+/// trailing newline). Returns `(expanded text, true if `\c` was hit)`.
+fn interpret_backslash_escapes(s: &str) -> (String, bool) {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('\\') => out.push('\\'),
+            Some('c') => return (out, true),
+            Some('0') => {
+                let mut digits = String::new();
+                while digits.len() < 3 {
+                    match chars.peek() {
+                        Some(d) if d.is_digit(8) => digits.push(*d),
+                        _ => break,
+                    }
+                    chars.next();
+                }
+                out.push(u8::from_str_radix(&digits, 8).unwrap_or(0) as char);
+            }
+            Some('x') => {
+                let mut digits = String::new();
+                while digits.len() < 2 {
+                    match chars.peek() {
+                        Some(d) if d.is_ascii_hexdigit() => digits.push(*d),
+                        _ => break,
+                    }
+                    chars.next();
+                }
+                if digits.is_empty() {
+                    out.push('\\');
+                    out.push('x');
+                } else {
+                    out.push(u8::from_str_radix(&digits, 16).unwrap_or(0) as char);
+                }
+            }
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+
+    (out, false)
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -131,8 +226,18 @@ mod tests {
 
     #[test]
     fn test_echo_with_dashes() {
+        // `-n` is a recognized flag so it's consumed; `--help` isn't a pure
+        // `n`/`e`/`E` flag word, so flag parsing stops there.
         let result = run_echo_binary(vec!["-n", "--help", "-"]);
-        assert_eq!(result.stdout, "-n --help -\n");
+        assert_eq!(result.stdout, "--help -");
+        assert_eq!(result.stderr, "");
+        assert_eq!(result.exit_code, 0);
+    }
+
+    #[test]
+    fn test_echo_unrecognized_flag_stops_parsing() {
+        let result = run_echo_binary(vec!["-x", "hello"]);
+        assert_eq!(result.stdout, "-x hello\n");
         assert_eq!(result.stderr, "");
         assert_eq!(result.exit_code, 0);
     }
@@ -152,4 +257,39 @@ mod tests {
         assert_eq!(result.stderr, "");
         assert_eq!(result.exit_code, 0);
     }
+
+    #[test]
+    fn test_echo_suppresses_trailing_newline() {
+        let result = run_echo_binary(vec!["-n", "hello"]);
+        assert_eq!(result.stdout, "hello");
+        assert_eq!(result.stderr, "");
+        assert_eq!(result.exit_code, 0);
+    }
+
+    #[test]
+    fn test_echo_interprets_escapes() {
+        let result = run_echo_binary(vec!["-e", "a\\tb"]);
+        assert_eq!(result.stdout, "a\tb\n");
+        assert_eq!(result.stderr, "");
+        assert_eq!(result.exit_code, 0);
+    }
+
+    #[test]
+    fn test_echo_double_dash_is_printed_literally() {
+        // POSIX/GNU echo has no `--` end-of-options marker, so it fails the
+        // pure-flag-word check and ends flag parsing immediately, leaving
+        // it (and everything after) in the output.
+        let result = run_echo_binary(vec!["--", "-n"]);
+        assert_eq!(result.stdout, "-- -n\n");
+        assert_eq!(result.stderr, "");
+        assert_eq!(result.exit_code, 0);
+    }
+
+    #[test]
+    fn test_echo_combined_flags() {
+        let result = run_echo_binary(vec!["-nE", "a\\tb"]);
+        assert_eq!(result.stdout, "a\\tb");
+        assert_eq!(result.stderr, "");
+        assert_eq!(result.exit_code, 0);
+    }
 }
@@ -3,31 +3,163 @@ use std::fs;
 use std::io::{self, Read, Write};
 use std::process;
 
-fn count_lines_words_bytes(content: &str) -> (usize, usize, usize) {
-    let bytes = content.len();
-    let lines = if content.is_empty() {
-        0
-    } else {
-        content.lines().count()
-    };
-    let words = content.split_whitespace().count();
+/// Parsed command-line flags, mirroring the coreutils subset of `wc`'s options.
+#[derive(Default, Clone, Copy)]
+struct Flags {
+    lines: bool,
+    words: bool,
+    bytes: bool,
+    chars: bool,
+    max_line_len: bool,
+}
+
+impl Flags {
+    fn any(&self) -> bool {
+        self.lines || self.words || self.bytes || self.chars || self.max_line_len
+    }
+}
+
+/// Splits `args` into flags and file operands, stopping flag parsing at `--`.
+/// A bare `-` is kept as a file operand meaning stdin.
+fn parse_args(args: &[String]) -> (Flags, Vec<&String>) {
+    let mut flags = Flags::default();
+    let mut files = Vec::new();
+    let mut no_more_flags = false;
+
+    for arg in args {
+        if no_more_flags || arg == "-" || !arg.starts_with('-') {
+            files.push(arg);
+            continue;
+        }
+        if arg == "--" {
+            no_more_flags = true;
+            continue;
+        }
+        match arg.as_str() {
+            "-l" => flags.lines = true,
+            "-w" => flags.words = true,
+            "-c" => flags.bytes = true,
+            "-m" => flags.chars = true,
+            "-L" => flags.max_line_len = true,
+            _ => {
+                eprintln!("wc: invalid option -- '{}'", arg);
+                process::exit(1);
+            }
+        }
+    }
+
+    (flags, files)
+}
+
+/// The counts `wc` can report for one file (or stdin). `chars` counts
+/// Unicode scalar values rather than bytes, so it can differ from `bytes`
+/// on non-ASCII content; `max_line_len` is the widest line's character
+/// count, used for `-L`.
+#[derive(Default, Clone, Copy)]
+struct Counts {
+    lines: usize,
+    words: usize,
+    bytes: usize,
+    chars: usize,
+    max_line_len: usize,
+}
 
-    (lines, words, bytes)
+impl Counts {
+    fn of(content: &str) -> Self {
+        let bytes = content.len();
+        let lines = if content.is_empty() {
+            0
+        } else {
+            content.lines().count()
+        };
+        let words = content.split_whitespace().count();
+        let chars = content.chars().count();
+        let max_line_len = content.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+
+        Self {
+            lines,
+            words,
+            bytes,
+            chars,
+            max_line_len,
+        }
+    }
+
+    fn add(&mut self, other: &Counts) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.bytes += other.bytes;
+        self.chars += other.chars;
+        self.max_line_len = self.max_line_len.max(other.max_line_len);
+    }
+}
+
+/// The columns to print, in the canonical l/w/c order — `flags.any()`
+/// selects a subset; no flags at all keeps the original lines+words+bytes
+/// default.
+fn selected_columns(flags: Flags) -> Vec<fn(&Counts) -> usize> {
+    if !flags.any() {
+        return vec![|c: &Counts| c.lines, |c: &Counts| c.words, |c: &Counts| c.bytes];
+    }
+
+    let mut columns: Vec<fn(&Counts) -> usize> = Vec::new();
+    if flags.lines {
+        columns.push(|c| c.lines);
+    }
+    if flags.words {
+        columns.push(|c| c.words);
+    }
+    if flags.bytes {
+        columns.push(|c| c.bytes);
+    }
+    if flags.chars {
+        columns.push(|c| c.chars);
+    }
+    if flags.max_line_len {
+        columns.push(|c| c.max_line_len);
+    }
+    columns
+}
+
+/// Prints one row per `(label, counts)` pair, right-aligning every selected
+/// column to the width of the largest value among all rows so a multi-file
+/// listing (including its `total` row) lines up instead of using a fixed
+/// `{:8}`. `label` is the filename, or `None` for stdin's unlabeled row.
+fn print_counts(rows: &[(Option<&str>, Counts)], flags: Flags, out: &mut impl Write) -> io::Result<()> {
+    let columns = selected_columns(flags);
+    let width = rows
+        .iter()
+        .flat_map(|(_, counts)| columns.iter().map(move |column| column(counts)))
+        .map(|value| value.to_string().len())
+        .max()
+        .unwrap_or(1);
+
+    for (label, counts) in rows {
+        let fields: Vec<String> = columns
+            .iter()
+            .map(|column| format!("{:>width$}", column(counts), width = width))
+            .collect();
+        match label {
+            Some(name) => writeln!(out, "{} {}", fields.join(" "), name)?,
+            None => writeln!(out, "{}", fields.join(" "))?,
+        }
+    }
+    Ok(())
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
+    let (flags, files) = parse_args(&args[1..]);
 
-    // Skip program name
-    let file_args: Vec<&String> = args.iter().skip(1).collect();
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
 
-    if file_args.is_empty() {
-        // Read from stdin if no files provided
+    if files.is_empty() {
         let mut input = String::new();
         match io::stdin().read_to_string(&mut input) {
             Ok(_) => {
-                let (lines, words, bytes) = count_lines_words_bytes(&input);
-                println!("{:8} {:8} {:8}", lines, words, bytes);
+                let counts = Counts::of(&input);
+                print_counts(&[(None, counts)], flags, &mut out).unwrap();
             }
             Err(e) => {
                 eprintln!("wc: error reading from stdin: {}", e);
@@ -35,22 +167,15 @@ fn main() {
             }
         }
     } else {
-        let mut total_lines = 0;
-        let mut total_words = 0;
-        let mut total_bytes = 0;
-        let mut file_count = 0;
+        let mut rows: Vec<(Option<&str>, Counts)> = Vec::new();
+        let mut total = Counts::default();
 
-        // Process each file
-        for file_path in &file_args {
+        for file_path in &files {
             match fs::read_to_string(file_path) {
                 Ok(content) => {
-                    let (lines, words, bytes) = count_lines_words_bytes(&content);
-                    println!("{:8} {:8} {:8} {}", lines, words, bytes, file_path);
-
-                    total_lines += lines;
-                    total_words += words;
-                    total_bytes += bytes;
-                    file_count += 1;
+                    let counts = Counts::of(&content);
+                    total.add(&counts);
+                    rows.push((Some(file_path.as_str()), counts));
                 }
                 Err(e) => {
                     eprintln!("wc: {}: {}", file_path, e);
@@ -59,17 +184,14 @@ fn main() {
             }
         }
 
-        // Show totals if more than one file
-        if file_count > 1 {
-            println!(
-                "{:8} {:8} {:8} total",
-                total_lines, total_words, total_bytes
-            );
+        if rows.len() > 1 {
+            rows.push((Some("total"), total));
         }
+        print_counts(&rows, flags, &mut out).unwrap();
     }
 
     // Ensure output is flushed
-    io::stdout().flush().unwrap();
+    out.flush().unwrap();
 }
 
 #[cfg(test)]
@@ -91,30 +213,44 @@ mod tests {
     }
 
     #[test]
-    fn test_count_lines_words_bytes() {
+    fn test_counts_of() {
         let content = "Hello world\nThis is a test\n";
-        let (lines, words, bytes) = count_lines_words_bytes(content);
-        assert_eq!(lines, 2);
-        assert_eq!(words, 6);
-        assert_eq!(bytes, content.len());
+        let counts = Counts::of(content);
+        assert_eq!(counts.lines, 2);
+        assert_eq!(counts.words, 6);
+        assert_eq!(counts.bytes, content.len());
+    }
+
+    #[test]
+    fn test_counts_of_empty_content() {
+        let counts = Counts::of("");
+        assert_eq!(counts.lines, 0);
+        assert_eq!(counts.words, 0);
+        assert_eq!(counts.bytes, 0);
+        assert_eq!(counts.chars, 0);
+        assert_eq!(counts.max_line_len, 0);
+    }
+
+    #[test]
+    fn test_counts_of_single_line_no_newline() {
+        let counts = Counts::of("Hello world");
+        assert_eq!(counts.lines, 1);
+        assert_eq!(counts.words, 2);
+        assert_eq!(counts.bytes, 11);
     }
 
     #[test]
-    fn test_count_empty_content() {
-        let content = "";
-        let (lines, words, bytes) = count_lines_words_bytes(content);
-        assert_eq!(lines, 0);
-        assert_eq!(words, 0);
-        assert_eq!(bytes, 0);
+    fn test_counts_of_multibyte_characters_counted_as_scalars() {
+        let content = "héllo\n";
+        let counts = Counts::of(content);
+        assert_eq!(counts.chars, 6);
+        assert!(counts.bytes > counts.chars);
     }
 
     #[test]
-    fn test_count_single_line_no_newline() {
-        let content = "Hello world";
-        let (lines, words, bytes) = count_lines_words_bytes(content);
-        assert_eq!(lines, 1);
-        assert_eq!(words, 2);
-        assert_eq!(bytes, 11);
+    fn test_counts_of_max_line_len() {
+        let counts = Counts::of("short\na much longer line\nmid\n");
+        assert_eq!(counts.max_line_len, "a much longer line".chars().count());
     }
 
     #[test]
@@ -266,4 +402,136 @@ mod tests {
 
         assert!(output.stderr.is_empty());
     }
+
+    #[test]
+    fn test_wc_lines_only_flag() {
+        let test_dir = env::temp_dir().join("wc_test_lines_only");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let test_file = test_dir.join("test.txt");
+        fs::write(&test_file, "a\nb\nc\n").expect("Failed to write test file");
+
+        let output = Command::new(get_wc_binary_path())
+            .arg("-l")
+            .arg(&test_file)
+            .output()
+            .expect("Failed to execute wc command");
+
+        assert!(output.status.success());
+        let stdout_str = String::from_utf8_lossy(&output.stdout);
+        let parts: Vec<&str> = stdout_str.split_whitespace().collect();
+        assert_eq!(parts.len(), 2); // lines filename
+        assert_eq!(parts[0], "3");
+        assert!(parts[1].contains("test.txt"));
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_wc_multiple_flags_print_in_canonical_order() {
+        let test_dir = env::temp_dir().join("wc_test_multi_flags");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let test_file = test_dir.join("test.txt");
+        let content = "one two\nthree\n";
+        fs::write(&test_file, content).expect("Failed to write test file");
+
+        // Flags given out of order; output columns should still follow l/w/c.
+        let output = Command::new(get_wc_binary_path())
+            .arg("-c")
+            .arg("-l")
+            .arg("-w")
+            .arg(&test_file)
+            .output()
+            .expect("Failed to execute wc command");
+
+        assert!(output.status.success());
+        let stdout_str = String::from_utf8_lossy(&output.stdout);
+        let parts: Vec<&str> = stdout_str.split_whitespace().collect();
+        assert_eq!(parts.len(), 4); // lines words bytes filename
+        assert_eq!(parts[0], "2");
+        assert_eq!(parts[1], "3");
+        assert_eq!(parts[2], &content.len().to_string());
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_wc_chars_flag_counts_unicode_scalars() {
+        let test_dir = env::temp_dir().join("wc_test_chars");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let test_file = test_dir.join("test.txt");
+        fs::write(&test_file, "héllo\n").expect("Failed to write test file");
+
+        let output = Command::new(get_wc_binary_path())
+            .arg("-m")
+            .arg(&test_file)
+            .output()
+            .expect("Failed to execute wc command");
+
+        assert!(output.status.success());
+        let stdout_str = String::from_utf8_lossy(&output.stdout);
+        let parts: Vec<&str> = stdout_str.split_whitespace().collect();
+        assert_eq!(parts[0], "6");
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_wc_longest_line_flag() {
+        let test_dir = env::temp_dir().join("wc_test_longest_line");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let test_file = test_dir.join("test.txt");
+        fs::write(&test_file, "short\na much longer line\nmid\n").expect("Failed to write test file");
+
+        let output = Command::new(get_wc_binary_path())
+            .arg("-L")
+            .arg(&test_file)
+            .output()
+            .expect("Failed to execute wc command");
+
+        assert!(output.status.success());
+        let stdout_str = String::from_utf8_lossy(&output.stdout);
+        let parts: Vec<&str> = stdout_str.split_whitespace().collect();
+        assert_eq!(parts[0], "a much longer line".len().to_string());
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_wc_column_width_adapts_to_largest_value() {
+        let test_dir = env::temp_dir().join("wc_test_width");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let small = test_dir.join("small.txt");
+        let big = test_dir.join("big.txt");
+        fs::write(&small, "a\n").expect("Failed to write small file");
+        fs::write(&big, "word ".repeat(20) + "\n").expect("Failed to write big file");
+
+        let output = Command::new(get_wc_binary_path())
+            .arg("-w")
+            .arg(&small)
+            .arg(&big)
+            .output()
+            .expect("Failed to execute wc command");
+
+        assert!(output.status.success());
+        let stdout_str = String::from_utf8_lossy(&output.stdout);
+        let lines: Vec<&str> = stdout_str.trim_end().split('\n').collect();
+        assert_eq!(lines.len(), 3);
+        // The widest value (21, the total) is 2 digits, so every row's
+        // single column should be right-aligned to width 2.
+        assert!(lines[0].starts_with(" 1 "));
+        assert!(lines[1].starts_with("20 "));
+        assert!(lines[2].starts_with("21 "));
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
 }
@@ -0,0 +1,546 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// A shell built-in. Unlike an external process, a built-in runs
+/// in-process and gets mutable access to the shell's own environment and
+/// working directory — the only way `cd` or an `export`-style assignment
+/// can persist across commands, since a spawned child's state dies with
+/// the child.
+pub trait Builtin {
+    fn run(
+        &self,
+        args: &[String],
+        stdin: Option<&str>,
+        env: &mut HashMap<String, String>,
+        cwd: &mut PathBuf,
+    ) -> io::Result<String>;
+}
+
+/// Looks built-ins up by name; checked before the bin-path/system
+/// fallback in `Runner::execute`. `with_defaults` ships the coreutils-style
+/// set (`cd`, `pwd`, `invocation_directory`, `echo`, `exit`, `wc`, `cat`);
+/// start from `new` and
+/// `register` to build a custom set at construction time.
+pub struct BuiltinRegistry {
+    builtins: HashMap<String, Box<dyn Builtin + Send + Sync>>,
+}
+
+impl BuiltinRegistry {
+    pub fn new() -> Self {
+        Self {
+            builtins: HashMap::new(),
+        }
+    }
+
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register("cd", Cd);
+        registry.register("pwd", Pwd);
+        registry.register("invocation_directory", InvocationDirectory);
+        registry.register("echo", Echo);
+        registry.register("exit", Exit);
+        registry.register("wc", Wc);
+        registry.register("cat", Cat);
+        registry
+    }
+
+    pub fn register<B: Builtin + Send + Sync + 'static>(&mut self, name: &str, builtin: B) {
+        self.builtins.insert(name.to_string(), Box::new(builtin));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&(dyn Builtin + Send + Sync)> {
+        self.builtins.get(name).map(|b| b.as_ref())
+    }
+
+    /// All registered builtin names, in no particular order — used by
+    /// `Completer` to offer them as first-word completion candidates.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.builtins.keys().map(String::as_str)
+    }
+}
+
+impl Default for BuiltinRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+/// Resolves `path` against `cwd`, expanding a leading `~` (or bare `~`) to
+/// `$HOME` first, the way every other shell's `cd` does.
+fn resolve_path(cwd: &Path, path: &str, env: &HashMap<String, String>) -> io::Result<PathBuf> {
+    let expanded;
+    let path = if path == "~" || path.starts_with("~/") {
+        let home = env
+            .get("HOME")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "cd: HOME not set"))?;
+        expanded = format!("{}{}", home, &path[1..]);
+        expanded.as_str()
+    } else {
+        path
+    };
+
+    let candidate = Path::new(path);
+    Ok(if candidate.is_absolute() {
+        candidate.to_path_buf()
+    } else {
+        cwd.join(candidate)
+    })
+}
+
+struct Cd;
+
+impl Builtin for Cd {
+    fn run(
+        &self,
+        args: &[String],
+        _stdin: Option<&str>,
+        env: &mut HashMap<String, String>,
+        cwd: &mut PathBuf,
+    ) -> io::Result<String> {
+        // `cd -` switches to `$OLDPWD` and prints it, same as bash.
+        let print_target = args.first().map(String::as_str) == Some("-");
+        let target = match args.first().map(String::as_str) {
+            Some("-") => env
+                .get("OLDPWD")
+                .map(PathBuf::from)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "cd: OLDPWD not set"))?,
+            Some(path) => resolve_path(cwd, path, env)?,
+            None => env
+                .get("HOME")
+                .map(PathBuf::from)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "cd: HOME not set"))?,
+        };
+
+        let canonical = target
+            .canonicalize()
+            .map_err(|e| io::Error::new(e.kind(), format!("cd: {}: {}", target.display(), e)))?;
+        if !canonical.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("cd: not a directory: {}", canonical.display()),
+            ));
+        }
+
+        env.insert("OLDPWD".to_string(), cwd.display().to_string());
+        *cwd = canonical;
+        env.insert("PWD".to_string(), cwd.display().to_string());
+        if print_target {
+            Ok(format!("{}\n", cwd.display()))
+        } else {
+            Ok(String::new())
+        }
+    }
+}
+
+struct Pwd;
+
+impl Builtin for Pwd {
+    fn run(
+        &self,
+        _args: &[String],
+        _stdin: Option<&str>,
+        _env: &mut HashMap<String, String>,
+        cwd: &mut PathBuf,
+    ) -> io::Result<String> {
+        Ok(format!("{}\n", cwd.display()))
+    }
+}
+
+/// Prints the working directory the shell was launched from (`Init`
+/// populates `$INVOCATION_DIRECTORY` once at startup; see
+/// `Init::invocation_directory`), not the current one — unlike `pwd`, this
+/// value never changes across a `cd`. Falls back to the current directory
+/// when run outside of `Init` (e.g. a bare `Runner` in tests), where no
+/// such variable has been set.
+struct InvocationDirectory;
+
+impl Builtin for InvocationDirectory {
+    fn run(
+        &self,
+        _args: &[String],
+        _stdin: Option<&str>,
+        env: &mut HashMap<String, String>,
+        cwd: &mut PathBuf,
+    ) -> io::Result<String> {
+        let dir = env
+            .get("INVOCATION_DIRECTORY")
+            .cloned()
+            .unwrap_or_else(|| cwd.display().to_string());
+        Ok(format!("{}\n", dir))
+    }
+}
+
+struct Echo;
+
+impl Builtin for Echo {
+    fn run(
+        &self,
+        args: &[String],
+        _stdin: Option<&str>,
+        _env: &mut HashMap<String, String>,
+        _cwd: &mut PathBuf,
+    ) -> io::Result<String> {
+        Ok(format!("{}\n", args.join(" ")))
+    }
+}
+
+struct Exit;
+
+/// Parses the optional numeric exit code `exit` was given, defaulting to
+/// 0 (success) the way bash's builtin does for a missing or non-numeric
+/// argument.
+fn parse_exit_code(args: &[String]) -> i32 {
+    args.first()
+        .and_then(|arg| arg.parse::<i32>().ok())
+        .unwrap_or(0)
+}
+
+impl Builtin for Exit {
+    fn run(
+        &self,
+        args: &[String],
+        _stdin: Option<&str>,
+        _env: &mut HashMap<String, String>,
+        _cwd: &mut PathBuf,
+    ) -> io::Result<String> {
+        process::exit(parse_exit_code(args));
+    }
+}
+
+/// Mirrors `src/bin/wc.rs`'s counting rules: `lines` counts lines the way
+/// `str::lines` does (a final line without a trailing newline still
+/// counts), `words` splits on whitespace, `bytes` is the raw byte length.
+fn count_lines_words_bytes(content: &str) -> (usize, usize, usize) {
+    let bytes = content.len();
+    let lines = if content.is_empty() {
+        0
+    } else {
+        content.lines().count()
+    };
+    let words = content.split_whitespace().count();
+    (lines, words, bytes)
+}
+
+struct Wc;
+
+impl Builtin for Wc {
+    fn run(
+        &self,
+        args: &[String],
+        stdin: Option<&str>,
+        env: &mut HashMap<String, String>,
+        cwd: &mut PathBuf,
+    ) -> io::Result<String> {
+        if args.is_empty() {
+            let (lines, words, bytes) = count_lines_words_bytes(stdin.unwrap_or(""));
+            return Ok(format!("{:8} {:8} {:8}\n", lines, words, bytes));
+        }
+
+        let mut output = String::new();
+        let mut total = (0, 0, 0);
+        for path in args {
+            let resolved = resolve_path(cwd, path, env)?;
+            let content = fs::read_to_string(&resolved)
+                .map_err(|e| io::Error::new(e.kind(), format!("wc: {}: {}", path, e)))?;
+            let (lines, words, bytes) = count_lines_words_bytes(&content);
+            output.push_str(&format!("{:8} {:8} {:8} {}\n", lines, words, bytes, path));
+            total.0 += lines;
+            total.1 += words;
+            total.2 += bytes;
+        }
+        if args.len() > 1 {
+            output.push_str(&format!(
+                "{:8} {:8} {:8} total\n",
+                total.0, total.1, total.2
+            ));
+        }
+        Ok(output)
+    }
+}
+
+struct Cat;
+
+impl Builtin for Cat {
+    fn run(
+        &self,
+        args: &[String],
+        stdin: Option<&str>,
+        env: &mut HashMap<String, String>,
+        cwd: &mut PathBuf,
+    ) -> io::Result<String> {
+        if args.is_empty() {
+            return Ok(stdin.unwrap_or("").to_string());
+        }
+
+        let mut output = String::new();
+        for path in args {
+            let resolved = resolve_path(cwd, path, env)?;
+            let content = fs::read_to_string(&resolved)
+                .map_err(|e| io::Error::new(e.kind(), format!("cat: {}: {}", path, e)))?;
+            output.push_str(&content);
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_registry_with_defaults_has_all_builtins() {
+        let registry = BuiltinRegistry::with_defaults();
+        for name in [
+            "cd",
+            "pwd",
+            "invocation_directory",
+            "echo",
+            "exit",
+            "wc",
+            "cat",
+        ] {
+            assert!(registry.get(name).is_some(), "missing builtin: {name}");
+        }
+        assert!(registry.get("definitely_not_a_builtin").is_none());
+    }
+
+    #[test]
+    fn test_registry_register_is_user_extensible() {
+        struct Noop;
+        impl Builtin for Noop {
+            fn run(
+                &self,
+                _args: &[String],
+                _stdin: Option<&str>,
+                _env: &mut HashMap<String, String>,
+                _cwd: &mut PathBuf,
+            ) -> io::Result<String> {
+                Ok("noop".to_string())
+            }
+        }
+
+        let mut registry = BuiltinRegistry::new();
+        assert!(registry.get("noop").is_none());
+        registry.register("noop", Noop);
+        assert!(registry.get("noop").is_some());
+    }
+
+    #[test]
+    fn test_echo_builtin_joins_args_with_newline() {
+        let mut env = HashMap::new();
+        let mut cwd = PathBuf::from("/tmp");
+        let output = Echo
+            .run(
+                &["hello".to_string(), "world".to_string()],
+                None,
+                &mut env,
+                &mut cwd,
+            )
+            .unwrap();
+        assert_eq!(output, "hello world\n");
+    }
+
+    #[test]
+    fn test_pwd_builtin_reports_cwd() {
+        let mut env = HashMap::new();
+        let mut cwd = PathBuf::from("/tmp/somewhere");
+        let output = Pwd.run(&[], None, &mut env, &mut cwd).unwrap();
+        assert_eq!(output, "/tmp/somewhere\n");
+    }
+
+    #[test]
+    fn test_cd_builtin_updates_cwd_and_oldpwd() {
+        let test_dir = env::temp_dir().join("cli_builtins_test_cd");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("create test dir");
+
+        let mut env = HashMap::new();
+        let mut cwd = env::temp_dir();
+        let target = test_dir.to_string_lossy().to_string();
+
+        Cd.run(&[target], None, &mut env, &mut cwd).unwrap();
+
+        assert_eq!(cwd, test_dir.canonicalize().unwrap());
+        assert_eq!(
+            env.get("OLDPWD"),
+            Some(&env::temp_dir().display().to_string())
+        );
+        assert_eq!(env.get("PWD"), Some(&cwd.display().to_string()));
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_cd_dash_toggles_back_to_oldpwd_and_prints_it() {
+        let test_dir = env::temp_dir().join("cli_builtins_test_cd_dash");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("create test dir");
+
+        let mut env = HashMap::new();
+        let mut cwd = env::temp_dir();
+        let start = cwd.clone();
+        let target = test_dir.to_string_lossy().to_string();
+
+        Cd.run(&[target], None, &mut env, &mut cwd).unwrap();
+        assert_eq!(cwd, test_dir.canonicalize().unwrap());
+
+        let output = Cd.run(&["-".to_string()], None, &mut env, &mut cwd).unwrap();
+
+        assert_eq!(cwd, start.canonicalize().unwrap());
+        assert_eq!(output, format!("{}\n", cwd.display()));
+        assert_eq!(env.get("OLDPWD"), Some(&test_dir.canonicalize().unwrap().display().to_string()));
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_cd_dash_without_oldpwd_errors() {
+        let mut env = HashMap::new();
+        let mut cwd = env::temp_dir();
+        let result = Cd.run(&["-".to_string()], None, &mut env, &mut cwd);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cd_expands_leading_tilde_to_home() {
+        let test_dir = env::temp_dir().join("cli_builtins_test_cd_tilde");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("create test dir");
+
+        let mut env = HashMap::new();
+        env.insert("HOME".to_string(), test_dir.to_string_lossy().to_string());
+        let mut cwd = env::temp_dir();
+
+        Cd.run(&["~".to_string()], None, &mut env, &mut cwd).unwrap();
+        assert_eq!(cwd, test_dir.canonicalize().unwrap());
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_invocation_directory_builtin_ignores_cd() {
+        let test_dir = env::temp_dir().join("cli_builtins_test_invocation_dir");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("create test dir");
+
+        let mut env = HashMap::new();
+        env.insert(
+            "INVOCATION_DIRECTORY".to_string(),
+            "/launched/from/here".to_string(),
+        );
+        let mut cwd = env::temp_dir();
+        let target = test_dir.to_string_lossy().to_string();
+
+        Cd.run(&[target], None, &mut env, &mut cwd).unwrap();
+        let output = InvocationDirectory
+            .run(&[], None, &mut env, &mut cwd)
+            .unwrap();
+
+        assert_eq!(output, "/launched/from/here\n");
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_invocation_directory_builtin_falls_back_to_cwd() {
+        let mut env = HashMap::new();
+        let mut cwd = PathBuf::from("/tmp/somewhere");
+        let output = InvocationDirectory
+            .run(&[], None, &mut env, &mut cwd)
+            .unwrap();
+        assert_eq!(output, "/tmp/somewhere\n");
+    }
+
+    #[test]
+    fn test_cd_builtin_rejects_nonexistent_path() {
+        let mut env = HashMap::new();
+        let mut cwd = env::temp_dir();
+        let result = Cd.run(
+            &["/nonexistent/definitely_missing_dir_12345".to_string()],
+            None,
+            &mut env,
+            &mut cwd,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cat_builtin_reads_stdin_when_no_args() {
+        let mut env = HashMap::new();
+        let mut cwd = env::temp_dir();
+        let output = Cat
+            .run(&[], Some("piped text\n"), &mut env, &mut cwd)
+            .unwrap();
+        assert_eq!(output, "piped text\n");
+    }
+
+    #[test]
+    fn test_cat_builtin_reads_file_args() {
+        let test_dir = env::temp_dir().join("cli_builtins_test_cat");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("create test dir");
+        let file = test_dir.join("a.txt");
+        fs::write(&file, "file contents\n").expect("write file");
+
+        let mut env = HashMap::new();
+        let mut cwd = env::temp_dir();
+        let output = Cat
+            .run(
+                &[file.to_string_lossy().to_string()],
+                None,
+                &mut env,
+                &mut cwd,
+            )
+            .unwrap();
+        assert_eq!(output, "file contents\n");
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_wc_builtin_counts_stdin() {
+        let mut env = HashMap::new();
+        let mut cwd = env::temp_dir();
+        let output = Wc
+            .run(&[], Some("one two\nthree\n"), &mut env, &mut cwd)
+            .unwrap();
+        let parts: Vec<&str> = output.split_whitespace().collect();
+        assert_eq!(parts, vec!["2", "3", "14"]);
+    }
+
+    #[test]
+    fn test_wc_builtin_counts_file_args() {
+        let test_dir = env::temp_dir().join("cli_builtins_test_wc");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("create test dir");
+        let file = test_dir.join("a.txt");
+        fs::write(&file, "one two\nthree\n").expect("write file");
+
+        let mut env = HashMap::new();
+        let mut cwd = env::temp_dir();
+        let output = Wc
+            .run(
+                &[file.to_string_lossy().to_string()],
+                None,
+                &mut env,
+                &mut cwd,
+            )
+            .unwrap();
+        assert!(output.contains("2"));
+        assert!(output.contains("3"));
+        assert!(output.contains("14"));
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_parse_exit_code_defaults_to_zero() {
+        assert_eq!(parse_exit_code(&[]), 0);
+        assert_eq!(parse_exit_code(&["not a number".to_string()]), 0);
+        assert_eq!(parse_exit_code(&["7".to_string()]), 7);
+    }
+}
@@ -1,3 +1,18 @@
+use std::collections::HashMap;
+
+/// A redirection on a descriptor other than stdin/stdout/stderr (fd >= 3) —
+/// `N>`, `N>>`, or `N<` — that `Runner` opens and wires into the spawned
+/// child via `dup2` once it already has fds 0/1/2 set up. Kept as its own
+/// list rather than named fields since those three have established,
+/// widely-depended-on call sites; higher fds are rarer and open-ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtraRedirect {
+    pub fd: u32,
+    pub path: String,
+    pub append: bool,
+    pub read: bool,
+}
+
 /// Common command structure used across the CLI
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Command {
@@ -8,6 +23,12 @@ pub struct Command {
     pub append_stdout: bool,
     pub stderr: Option<String>,
     pub append_stderr: bool,
+    pub merge_stderr_into_stdout: bool,
+    pub extra_redirects: Vec<ExtraRedirect>,
+    /// Per-command environment overrides (`FOO=bar cmd`) — applied on top
+    /// of the runner's base environment for this command's child process
+    /// only, the way `Runner::spawn_stage` layers them.
+    pub env: HashMap<String, String>,
 }
 
 impl Command {
@@ -20,6 +41,9 @@ impl Command {
             append_stdout: false,
             stderr: None,
             append_stderr: false,
+            merge_stderr_into_stdout: false,
+            extra_redirects: Vec::new(),
+            env: HashMap::new(),
         }
     }
 
@@ -47,6 +71,42 @@ impl Command {
         self.append_stderr = append;
         self
     }
+
+    /// Marks this command as `2>&1`: stderr is routed to the same
+    /// destination as stdout instead of wherever `stderr`/`append_stderr`
+    /// point, matching shell redirection order-of-operations.
+    pub fn with_stderr_to_stdout(mut self) -> Self {
+        self.merge_stderr_into_stdout = true;
+        self
+    }
+
+    /// Adds a redirection on `fd` (>= 3) to `path`, opened for reading when
+    /// `read` is set, otherwise for writing (truncating unless `append` is
+    /// set) — the generalized form of `with_stdout`/`with_stderr` for fds
+    /// the builder doesn't name directly.
+    pub fn with_extra_redirect<S: Into<String>>(
+        mut self,
+        fd: u32,
+        path: S,
+        append: bool,
+        read: bool,
+    ) -> Self {
+        self.extra_redirects.push(ExtraRedirect {
+            fd,
+            path: path.into(),
+            append,
+            read,
+        });
+        self
+    }
+
+    /// Sets `key` to `value` in this command's child process only, without
+    /// touching the runner's base environment — the builder-side half of
+    /// inline `FOO=bar cmd` assignments.
+    pub fn with_env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
 }
 
 #[cfg(test)]
@@ -113,4 +173,57 @@ mod tests {
         assert!(!cmd.append_stdout);
         assert!(cmd.append_stderr);
     }
+
+    #[test]
+    fn test_command_with_stderr_to_stdout() {
+        let cmd = Command::new("test", vec![])
+            .with_stdout("out.txt")
+            .with_stderr_to_stdout();
+
+        assert_eq!(cmd.stdout.unwrap(), "out.txt");
+        assert!(cmd.merge_stderr_into_stdout);
+        assert!(cmd.stderr.is_none());
+    }
+
+    #[test]
+    fn test_command_with_extra_redirect() {
+        let cmd = Command::new("test", vec![])
+            .with_extra_redirect(3, "file3.txt", false, false)
+            .with_extra_redirect(4, "file4.log", true, false)
+            .with_extra_redirect(5, "input5.txt", false, true);
+
+        assert_eq!(
+            cmd.extra_redirects,
+            vec![
+                ExtraRedirect {
+                    fd: 3,
+                    path: "file3.txt".to_string(),
+                    append: false,
+                    read: false,
+                },
+                ExtraRedirect {
+                    fd: 4,
+                    path: "file4.log".to_string(),
+                    append: true,
+                    read: false,
+                },
+                ExtraRedirect {
+                    fd: 5,
+                    path: "input5.txt".to_string(),
+                    append: false,
+                    read: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_with_env() {
+        let cmd = Command::new("test", vec![])
+            .with_env("FOO", "bar")
+            .with_env("BAZ", "qux");
+
+        assert_eq!(cmd.env.get("FOO"), Some(&"bar".to_string()));
+        assert_eq!(cmd.env.get("BAZ"), Some(&"qux".to_string()));
+    }
 }
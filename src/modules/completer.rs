@@ -0,0 +1,350 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::modules::builtins::BuiltinRegistry;
+
+/// Implemented by anything that can offer tab-completion candidates for a
+/// line and cursor position, so a line editor can depend on this trait
+/// instead of `ShellCompleter` directly. `ShellCompleter` is this crate's
+/// only implementation, but keeping the trait separate from it matches how
+/// `Builtin`/`BuiltinRegistry` split "the thing a caller depends on" from
+/// "the thing that actually does it."
+pub trait Completer {
+    /// Returns sorted, de-duplicated completions for the word ending at
+    /// `cursor` in `line` (`cursor` is clamped to `line`'s length).
+    fn complete(&self, line: &str, cursor: usize) -> Vec<String>;
+}
+
+/// Tab-completion, analogous to MOROS's `shell_completer`: given the
+/// current input line and cursor position, returns candidate completions
+/// for the word at the cursor. A word starting with `$` completes against
+/// defined variable names; the first word otherwise completes against
+/// builtins, custom binaries (`bin_path`/`extra_bin_paths`, the same
+/// directories `Runner` resolves against), and the system `PATH`; every
+/// later word completes against filesystem paths relative to `cwd`. A line
+/// editor wires `complete` to the Tab key itself — this type has no
+/// terminal dependency of its own.
+pub struct ShellCompleter {
+    bin_path: PathBuf,
+    extra_bin_paths: Vec<PathBuf>,
+    env_vars: HashMap<String, String>,
+    builtins: BuiltinRegistry,
+    cwd: PathBuf,
+}
+
+impl ShellCompleter {
+    pub fn new(bin_path: PathBuf, env_vars: HashMap<String, String>) -> Self {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self {
+            bin_path,
+            extra_bin_paths: Vec::new(),
+            env_vars,
+            builtins: BuiltinRegistry::with_defaults(),
+            cwd,
+        }
+    }
+
+    /// Adds another directory to search for a custom binary, mirroring
+    /// `Runner::add_bin_path`.
+    pub fn add_bin_path(&mut self, path: PathBuf) {
+        self.extra_bin_paths.push(path);
+    }
+
+    /// Sets the directory path completion resolves against. Defaults to
+    /// the process's current directory at construction time.
+    pub fn set_cwd(&mut self, cwd: PathBuf) {
+        self.cwd = cwd;
+    }
+
+    /// Replaces the variable names offered after `$` and consulted for
+    /// `PATH` — a caller whose session environment changes (an assignment,
+    /// `cd`, `export`-style builtin) calls this to keep completions current,
+    /// mirroring `set_cwd` above.
+    pub fn set_env_vars(&mut self, env_vars: HashMap<String, String>) {
+        self.env_vars = env_vars;
+    }
+
+    fn complete_variable(&self, prefix: &str) -> Vec<String> {
+        let name_prefix = &prefix[1..];
+        self.env_vars
+            .keys()
+            .filter(|name| name.starts_with(name_prefix))
+            .map(|name| format!("${name}"))
+            .collect()
+    }
+
+    fn complete_command(&self, prefix: &str) -> Vec<String> {
+        let mut names: HashSet<String> = HashSet::new();
+
+        names.extend(
+            self.builtins
+                .names()
+                .filter(|name| name.starts_with(prefix))
+                .map(str::to_string),
+        );
+
+        for dir in std::iter::once(&self.bin_path).chain(self.extra_bin_paths.iter()) {
+            names.extend(entries_starting_with(dir, prefix));
+        }
+
+        if let Some(path_var) = self.env_vars.get("PATH") {
+            for dir in std::env::split_paths(path_var) {
+                names.extend(entries_starting_with(&dir, prefix));
+            }
+        }
+
+        names.into_iter().collect()
+    }
+
+    fn complete_path(&self, prefix: &str) -> Vec<String> {
+        let (dir, file_prefix) = match prefix.rsplit_once('/') {
+            Some((dir, file_prefix)) => (self.cwd.join(dir), file_prefix),
+            None => (self.cwd.clone(), prefix),
+        };
+        let leading = prefix.rsplit_once('/').map(|(dir, _)| format!("{dir}/"));
+
+        let Ok(read_dir) = fs::read_dir(&dir) else {
+            return Vec::new();
+        };
+
+        let mut matches = Vec::new();
+        for entry in read_dir.filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with(file_prefix) {
+                continue;
+            }
+            if file_prefix.is_empty() && name.starts_with('.') {
+                continue;
+            }
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            let candidate = match &leading {
+                Some(prefix_dir) => format!("{prefix_dir}{name}"),
+                None => name,
+            };
+            matches.push(if is_dir {
+                format!("{candidate}/")
+            } else {
+                candidate
+            });
+        }
+        matches
+    }
+}
+
+impl Completer for ShellCompleter {
+    fn complete(&self, line: &str, cursor: usize) -> Vec<String> {
+        let pos = cursor.min(line.len());
+        let before_cursor = &line[..pos];
+        let word_start = word_boundary(before_cursor);
+        let word = &before_cursor[word_start..];
+        let is_first_word = is_first_word_of_pipeline_stage(&before_cursor[..word_start]);
+
+        let mut candidates = if word.starts_with('$') {
+            self.complete_variable(word)
+        } else if is_first_word {
+            self.complete_command(word)
+        } else {
+            self.complete_path(word)
+        };
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+}
+
+/// Finds the byte offset where the word under the cursor starts, the same
+/// way `Tokenizer` finds token boundaries: whitespace only ends a word
+/// outside of a quote, so `"foo bar` is one in-progress word, not two.
+fn word_boundary(before_cursor: &str) -> usize {
+    let mut quote: Option<char> = None;
+    let mut boundary = 0;
+    let mut chars = before_cursor.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        match quote {
+            Some(q) if c == q => {
+                quote = None;
+                continue;
+            }
+            Some(_) => continue,
+            None => {}
+        }
+        match c {
+            '\'' | '"' => quote = Some(c),
+            '\\' => {
+                chars.next();
+            }
+            ' ' | '\t' => boundary = idx + c.len_utf8(),
+            _ => {}
+        }
+    }
+    boundary
+}
+
+/// Whether the word starting right after `before_word` is the first word of
+/// its pipeline stage — true at the very start of the line, or right after
+/// an unquoted `|` — so completion offers commands (not paths) for the first
+/// word of every stage, not just the line's very first word.
+fn is_first_word_of_pipeline_stage(before_word: &str) -> bool {
+    if before_word.trim().is_empty() {
+        return true;
+    }
+    match crate::modules::input::tokenizer::Tokenizer::tokenize(before_word) {
+        Ok(tokens) => tokens.last().map(String::as_str) == Some("|"),
+        Err(_) => false,
+    }
+}
+
+/// Names directly inside `dir` that start with `prefix`, or an empty list
+/// if `dir` doesn't exist or can't be read.
+fn entries_starting_with(dir: &std::path::Path, prefix: &str) -> Vec<String> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    read_dir
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(prefix))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn setup_fixture_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "completer_test_{name}_{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create fixture dir");
+        dir
+    }
+
+    #[test]
+    fn test_complete_first_word_offers_builtins() {
+        let completer = ShellCompleter::new(PathBuf::from("/nonexistent/bin"), HashMap::new());
+        let candidates = completer.complete("ec", 2);
+        assert!(candidates.contains(&"echo".to_string()));
+    }
+
+    #[test]
+    fn test_complete_first_word_offers_custom_binaries() {
+        let bin_dir = setup_fixture_dir("custom_bin");
+        fs::write(bin_dir.join("mytool"), "").expect("write mytool");
+
+        let completer = ShellCompleter::new(bin_dir.clone(), HashMap::new());
+        let candidates = completer.complete("my", 2);
+        assert!(candidates.contains(&"mytool".to_string()));
+
+        let _ = fs::remove_dir_all(&bin_dir);
+    }
+
+    #[test]
+    fn test_complete_first_word_of_a_later_pipeline_stage_offers_commands() {
+        let completer = ShellCompleter::new(PathBuf::from("/nonexistent/bin"), HashMap::new());
+
+        let line = "echo hi | ec";
+        let candidates = completer.complete(line, line.len());
+        assert!(candidates.contains(&"echo".to_string()));
+    }
+
+    #[test]
+    fn test_word_boundary_ignores_whitespace_inside_an_open_quote() {
+        let before_cursor = r#"cat "a b"#;
+        // The space is inside the still-open double quote, so the whole
+        // quoted word is in progress — the boundary is where `"a b` starts,
+        // not after its inner space.
+        assert_eq!(word_boundary(before_cursor), "cat ".len());
+    }
+
+    #[test]
+    fn test_complete_later_word_offers_paths() {
+        let dir = setup_fixture_dir("paths");
+        fs::write(dir.join("readme.txt"), "").expect("write readme.txt");
+        fs::create_dir_all(dir.join("sub")).expect("create sub");
+
+        let mut completer = ShellCompleter::new(PathBuf::from("/nonexistent/bin"), HashMap::new());
+        completer.set_cwd(dir.clone());
+
+        let line = "cat read";
+        let candidates = completer.complete(line, line.len());
+        assert!(candidates.contains(&"readme.txt".to_string()));
+
+        let line = "cat s";
+        let candidates = completer.complete(line, line.len());
+        assert!(candidates.contains(&"sub/".to_string()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_complete_path_hides_dotfiles_unless_requested() {
+        let dir = setup_fixture_dir("dotfiles");
+        fs::write(dir.join(".hidden"), "").expect("write .hidden");
+        fs::write(dir.join("visible.txt"), "").expect("write visible.txt");
+
+        let mut completer = ShellCompleter::new(PathBuf::from("/nonexistent/bin"), HashMap::new());
+        completer.set_cwd(dir.clone());
+
+        let line = "cat ";
+        let candidates = completer.complete(line, line.len());
+        assert!(!candidates.iter().any(|c| c == ".hidden"));
+        assert!(candidates.contains(&"visible.txt".to_string()));
+
+        let line = "cat .";
+        let candidates = completer.complete(line, line.len());
+        assert!(candidates.contains(&".hidden".to_string()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_complete_results_are_sorted_and_deduped() {
+        let bin_dir = setup_fixture_dir("sorted");
+        fs::write(bin_dir.join("zeta"), "").expect("write zeta");
+        fs::write(bin_dir.join("alpha"), "").expect("write alpha");
+
+        let mut completer = ShellCompleter::new(bin_dir.clone(), HashMap::new());
+        completer.add_bin_path(bin_dir.clone());
+        let candidates = completer.complete("", 0);
+
+        let mut sorted = candidates.clone();
+        sorted.sort();
+        assert_eq!(candidates, sorted);
+
+        let alpha_count = candidates.iter().filter(|c| c.as_str() == "alpha").count();
+        assert_eq!(alpha_count, 1);
+
+        let _ = fs::remove_dir_all(&bin_dir);
+    }
+
+    #[test]
+    fn test_complete_dollar_word_offers_variable_names() {
+        let mut env_vars = HashMap::new();
+        env_vars.insert("HOME".to_string(), "/home/user".to_string());
+        env_vars.insert("HOSTNAME".to_string(), "box".to_string());
+        env_vars.insert("SHELL".to_string(), "/bin/sh".to_string());
+
+        let completer = ShellCompleter::new(PathBuf::from("/nonexistent/bin"), env_vars);
+        let candidates = completer.complete("echo $HO", 8);
+
+        assert!(candidates.contains(&"$HOME".to_string()));
+        assert!(candidates.contains(&"$HOSTNAME".to_string()));
+        assert!(!candidates.contains(&"$SHELL".to_string()));
+    }
+
+    #[test]
+    fn test_set_env_vars_refreshes_variable_completions() {
+        let mut completer = ShellCompleter::new(PathBuf::from("/nonexistent/bin"), HashMap::new());
+        assert_eq!(completer.complete("$FO", 3), Vec::<String>::new());
+
+        let mut env_vars = HashMap::new();
+        env_vars.insert("FOO".to_string(), "bar".to_string());
+        completer.set_env_vars(env_vars);
+
+        assert_eq!(completer.complete("$FO", 3), vec!["$FOO".to_string()]);
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    vars: HashMap<String, String>,
+    /// User-defined `alias name=value` pairs, mirroring the `aliases:
+    /// BTreeMap<String, String>` in MOROS's shell `Config`. A `BTreeMap`
+    /// keeps iteration (e.g. a future `alias` with no arguments listing
+    /// everything defined) in a stable, sorted order.
+    aliases: BTreeMap<String, String>,
+}
+impl Environment {
+    pub fn new() -> Self {
+        Self {
+            vars: HashMap::new(),
+            aliases: BTreeMap::new(),
+        }
+    }
+    pub fn with_vars(vars: HashMap<String, String>) -> Self {
+        Self {
+            vars,
+            aliases: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.vars.get(key).map(|s| s.as_str())
+    }
+    pub fn set<K: Into<String>, V: Into<String>>(&mut self, k: K, v: V) {
+        self.vars.insert(k.into(), v.into());
+    }
+    pub fn remove(&mut self, k: &str) {
+        self.vars.remove(k);
+    }
+    /// All currently-set variables, in arbitrary order — lets a caller
+    /// (e.g. `ShellCompleter`) build a name-prefix candidate list without
+    /// needing a getter for every name up front.
+    pub fn vars(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.vars.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    pub fn capture_current() -> Self {
+        let vars = std::env::vars().collect::<HashMap<_, _>>();
+        Self {
+            vars,
+            aliases: BTreeMap::new(),
+        }
+    }
+
+    /// Looks up a defined `alias`, returning its (unexpanded) value.
+    pub fn get_alias(&self, name: &str) -> Option<&str> {
+        self.aliases.get(name).map(|s| s.as_str())
+    }
+    /// Defines or redefines `alias name=value`.
+    pub fn set_alias<K: Into<String>, V: Into<String>>(&mut self, name: K, value: V) {
+        self.aliases.insert(name.into(), value.into());
+    }
+    /// Removes `alias name`, the `unalias` builtin.
+    pub fn remove_alias(&mut self, name: &str) {
+        self.aliases.remove(name);
+    }
+    /// All defined aliases, in name order.
+    pub fn aliases(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
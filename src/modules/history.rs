@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::PathBuf;
+
+/// Tracks every non-empty line entered at the `Repl` prompt and persists
+/// it to a dotfile (`~/.cli_rust_history` by default), the same history
+/// pattern MOROS and oursh give their shells. Capped at `max_size`
+/// entries (oldest dropped first) and consecutive duplicate lines are
+/// folded into one, so re-running the same command repeatedly doesn't
+/// spam the list.
+pub struct History {
+    path: PathBuf,
+    max_size: usize,
+    entries: Vec<String>,
+}
+
+impl History {
+    /// Loads existing entries from `path` (starting empty if it doesn't
+    /// exist yet or can't be read), capped at `max_size`.
+    pub fn load(path: PathBuf, max_size: usize) -> Self {
+        let entries = fs::read_to_string(&path)
+            .map(|contents| contents.lines().map(String::from).collect())
+            .unwrap_or_default();
+        let mut history = Self {
+            path,
+            max_size,
+            entries,
+        };
+        history.truncate();
+        history
+    }
+
+    /// The default `~/.cli_rust_history` path, falling back to the
+    /// current directory if `HOME` isn't set (e.g. some test sandboxes).
+    pub fn default_path() -> PathBuf {
+        let home = std::env::var("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("."));
+        home.join(".cli_rust_history")
+    }
+
+    /// Records `line`, skipping it if it's identical to the previous
+    /// entry, then re-persists the dotfile and truncates in memory if
+    /// over `max_size`.
+    pub fn record(&mut self, line: &str) {
+        if self.entries.last().map(String::as_str) == Some(line) {
+            return;
+        }
+        self.entries.push(line.to_string());
+        self.truncate();
+        let _ = fs::write(&self.path, self.entries.join("\n") + "\n");
+    }
+
+    fn truncate(&mut self) {
+        if self.entries.len() > self.max_size {
+            let excess = self.entries.len() - self.max_size;
+            self.entries.drain(0..excess);
+        }
+    }
+
+    /// All stored entries, oldest first — `1` is the first entry, matching
+    /// both `!N` and the `history` builtin's numbering.
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Resolves a `!!` (the previous entry) or `!N` (the Nth entry,
+    /// 1-indexed) re-execution reference against the entries recorded so
+    /// far. Returns `None` for anything else, meaning `line` should run
+    /// as-is rather than being expanded.
+    pub fn expand(&self, line: &str) -> Option<String> {
+        if line == "!!" {
+            return self.entries.last().cloned();
+        }
+        let rest = line.strip_prefix('!')?;
+        let n: usize = rest.parse().ok()?;
+        if n == 0 {
+            return None;
+        }
+        self.entries.get(n - 1).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn scratch_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("cli_history_test_{}_{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_load_starts_empty_when_file_missing() {
+        let path = scratch_path("missing");
+        let _ = fs::remove_file(&path);
+        let history = History::load(path, 100);
+        assert!(history.entries().is_empty());
+    }
+
+    #[test]
+    fn test_record_appends_and_persists() {
+        let path = scratch_path("record");
+        let _ = fs::remove_file(&path);
+        let mut history = History::load(path.clone(), 100);
+
+        history.record("echo hi");
+        history.record("ls -la");
+
+        assert_eq!(history.entries(), ["echo hi", "ls -la"]);
+        let reloaded = History::load(path.clone(), 100);
+        assert_eq!(reloaded.entries(), ["echo hi", "ls -la"]);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_record_dedups_consecutive_identical_lines() {
+        let path = scratch_path("dedup");
+        let _ = fs::remove_file(&path);
+        let mut history = History::load(path.clone(), 100);
+
+        history.record("echo hi");
+        history.record("echo hi");
+        history.record("ls");
+        history.record("echo hi");
+
+        assert_eq!(history.entries(), ["echo hi", "ls", "echo hi"]);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_record_caps_at_max_size() {
+        let path = scratch_path("cap");
+        let _ = fs::remove_file(&path);
+        let mut history = History::load(path.clone(), 2);
+
+        history.record("one");
+        history.record("two");
+        history.record("three");
+
+        assert_eq!(history.entries(), ["two", "three"]);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_expand_bang_bang_resolves_previous_entry() {
+        let path = scratch_path("bang_bang");
+        let _ = fs::remove_file(&path);
+        let mut history = History::load(path.clone(), 100);
+        history.record("echo hi");
+
+        assert_eq!(history.expand("!!"), Some("echo hi".to_string()));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_expand_bang_n_resolves_nth_entry() {
+        let path = scratch_path("bang_n");
+        let _ = fs::remove_file(&path);
+        let mut history = History::load(path.clone(), 100);
+        history.record("echo one");
+        history.record("echo two");
+
+        assert_eq!(history.expand("!1"), Some("echo one".to_string()));
+        assert_eq!(history.expand("!2"), Some("echo two".to_string()));
+        assert_eq!(history.expand("!99"), None);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_expand_returns_none_for_non_reference_lines() {
+        let history = History::load(scratch_path("not_a_ref"), 100);
+        assert_eq!(history.expand("echo hi"), None);
+        assert_eq!(history.expand("!notanumber"), None);
+    }
+}
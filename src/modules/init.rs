@@ -1,7 +1,7 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::modules::input::{Environment, InputProcessor, InputProcessorBuilder};
+use crate::modules::input::{InputProcessor, InputProcessorBuilder};
 
 #[derive(Debug, Clone)]
 pub struct Init {
@@ -9,6 +9,12 @@ pub struct Init {
     pub env_vars: HashMap<String, String>,
     /// Binary path for implemented commands
     pub bin_path: PathBuf,
+    /// The working directory the shell was launched from, captured once at
+    /// startup and never updated by `cd` — unlike `$PWD`/`$OLDPWD`, which
+    /// track the *current* directory. Mirrors `just`'s
+    /// `invocation_directory()`, letting scripts reference paths relative
+    /// to where the user ran the shell even after changing directories.
+    pub invocation_directory: PathBuf,
 }
 
 impl Init {
@@ -38,13 +44,84 @@ impl Init {
             }
         };
 
-        Init { env_vars, bin_path }
+        let invocation_directory =
+            std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let mut init = Init {
+            env_vars,
+            bin_path,
+            invocation_directory,
+        };
+        init.env_vars.insert(
+            "INVOCATION_DIRECTORY".to_string(),
+            init.invocation_directory.display().to_string(),
+        );
+        init.load_dotenv();
+        init
     }
 
     /// Create a new Init with custom environment variables and binary path.
     /// Suitable for testing
     pub fn with_config(env_vars: HashMap<String, String>, bin_path: PathBuf) -> Self {
-        Init { env_vars, bin_path }
+        Init {
+            env_vars,
+            bin_path,
+            invocation_directory: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        }
+    }
+
+    /// The working directory the shell was launched from. See the field
+    /// doc comment on `Init::invocation_directory`.
+    pub fn invocation_directory(&self) -> &Path {
+        &self.invocation_directory
+    }
+
+    /// Walks from the current directory upward to the filesystem root
+    /// looking for a `.env` file and merges it in, the way `Init::new`
+    /// does automatically on startup. A no-op if none is found.
+    pub fn load_dotenv(&mut self) {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        if let Some(path) = Self::find_dotenv_upward(&cwd) {
+            self.merge_dotenv_file(&path);
+        }
+    }
+
+    /// Merges a specific `.env`-style file into `env_vars`. Lets tests point
+    /// at a fixture file instead of relying on directory discovery.
+    pub fn with_dotenv<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.merge_dotenv_file(path.as_ref());
+        self
+    }
+
+    /// Searches `dir` and each of its ancestors, in order, for a `.env`
+    /// file, mirroring how tools like `just` discover project config from
+    /// whatever subdirectory a command is run in.
+    fn find_dotenv_upward(dir: &Path) -> Option<PathBuf> {
+        let mut current = Some(dir.to_path_buf());
+        while let Some(d) = current {
+            let candidate = d.join(".env");
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+            current = d.parent().map(Path::to_path_buf);
+        }
+        None
+    }
+
+    /// Parses `path` as `KEY=value` lines and merges the result into
+    /// `env_vars`, skipping any key already set in `env_vars` or in the
+    /// real process environment — dotenv fills gaps, it never overrides a
+    /// variable the user or the OS already set. Silently does nothing if
+    /// `path` can't be read.
+    fn merge_dotenv_file(&mut self, path: &Path) {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return;
+        };
+        for (key, value) in parse_dotenv(&contents) {
+            if self.env_vars.contains_key(&key) || std::env::var(&key).is_ok() {
+                continue;
+            }
+            self.env_vars.insert(key, value);
+        }
     }
 
     /// Get an environment variable value
@@ -69,6 +146,47 @@ impl Default for Init {
     }
 }
 
+/// Parses the contents of a `.env`-style file into `(key, value)` pairs,
+/// in file order. Blank lines and `#`-prefixed comments are skipped, an
+/// optional `export ` prefix (as in `export KEY=value`) is stripped, and a
+/// value wrapped in matching single or double quotes has those quotes
+/// removed. Malformed lines (no `=`, or an empty key) are skipped rather
+/// than rejecting the whole file.
+fn parse_dotenv(contents: &str) -> Vec<(String, String)> {
+    let mut vars = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            continue;
+        }
+
+        vars.push((key.to_string(), strip_matching_quotes(value.trim())));
+    }
+    vars
+}
+
+/// Strips a leading and trailing quote from `value` if they match (either
+/// `"..."` or `'...'`), otherwise returns it unchanged.
+fn strip_matching_quotes(value: &str) -> String {
+    let bytes = value.as_bytes();
+    if bytes.len() >= 2 {
+        let (first, last) = (bytes[0], bytes[bytes.len() - 1]);
+        if (first == b'"' && last == b'"') || (first == b'\'' && last == b'\'') {
+            return value[1..value.len() - 1].to_string();
+        }
+    }
+    value.to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,6 +198,17 @@ mod tests {
         assert!(init.bin_path.to_string_lossy().contains("target"));
     }
 
+    #[test]
+    fn test_init_captures_invocation_directory() {
+        let init = Init::new();
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(init.invocation_directory(), cwd.as_path());
+        assert_eq!(
+            init.get_env("INVOCATION_DIRECTORY"),
+            Some(&cwd.display().to_string())
+        );
+    }
+
     #[test]
     fn test_with_config_init() {
         let mut env_vars = HashMap::new();
@@ -97,9 +226,88 @@ mod tests {
         init.set_env("NEW_VAR".to_string(), "new_value".to_string());
         assert_eq!(init.get_env("NEW_VAR"), Some(&"new_value".to_string()));
     }
+
+    #[test]
+    fn test_parse_dotenv_basic_and_comments() {
+        let contents = "\
+# a comment
+FOO=bar
+
+BAZ=qux # not a comment, just part of the value
+";
+        let vars = parse_dotenv(contents);
+        assert_eq!(
+            vars,
+            vec![
+                ("FOO".to_string(), "bar".to_string()),
+                ("BAZ".to_string(), "qux # not a comment, just part of the value".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_dotenv_export_prefix_and_quotes() {
+        let contents = "export NAME=\"quoted value\"\nexport SINGLE='single quoted'\nPLAIN=unquoted";
+        let vars = parse_dotenv(contents);
+        assert_eq!(
+            vars,
+            vec![
+                ("NAME".to_string(), "quoted value".to_string()),
+                ("SINGLE".to_string(), "single quoted".to_string()),
+                ("PLAIN".to_string(), "unquoted".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_dotenv_skips_malformed_lines() {
+        let contents = "no_equals_sign\n=no_key\nGOOD=value";
+        let vars = parse_dotenv(contents);
+        assert_eq!(vars, vec![("GOOD".to_string(), "value".to_string())]);
+    }
+
+    #[test]
+    fn test_with_dotenv_fills_gaps_without_overriding() {
+        let dir = std::env::temp_dir().join("cli_init_dotenv_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("failed to create test dir");
+        let dotenv_path = dir.join(".env");
+        std::fs::write(&dotenv_path, "DOTENV_ONLY=from_file\nPATH=should_not_override\n")
+            .expect("failed to write fixture .env");
+
+        let mut env_vars = HashMap::new();
+        env_vars.insert("PATH".to_string(), "/already/set".to_string());
+        let init =
+            Init::with_config(env_vars, PathBuf::from("/test_path/bin")).with_dotenv(&dotenv_path);
+
+        assert_eq!(init.get_env("DOTENV_ONLY"), Some(&"from_file".to_string()));
+        assert_eq!(init.get_env("PATH"), Some(&"/already/set".to_string()));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_find_dotenv_upward_from_nested_directory() {
+        let root = std::env::temp_dir().join("cli_init_dotenv_walk_test");
+        let _ = std::fs::remove_dir_all(&root);
+        let nested = root.join("a").join("b");
+        std::fs::create_dir_all(&nested).expect("failed to create nested test dir");
+        std::fs::write(root.join(".env"), "FOUND_BY_WALK=yes\n")
+            .expect("failed to write fixture .env");
+
+        let found = Init::find_dotenv_upward(&nested);
+        assert_eq!(found, Some(root.join(".env")));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
 }
 
-pub fn build_input_processor() -> InputProcessor {
-    let env = Environment::capture_current(); // реальные переменные окружения
-    InputProcessorBuilder::new(env).build()
+/// Builds a plain `InputProcessor` with no command-substitution executor
+/// wired in — `Repl::new` builds its own instead, since only it has a
+/// `Runner` handle to wire `$(...)`/backtick substitution through. Variables
+/// themselves aren't affected by this: `init.env_vars()` (including any
+/// `.env`-sourced ones) are threaded into `$VAR` expansion by the caller's
+/// own `Environment`, passed separately to each `process` call.
+pub fn build_input_processor(_init: &Init) -> InputProcessor {
+    InputProcessorBuilder::new().build()
 }
@@ -0,0 +1,17 @@
+/// A sequence of statements executed in order; the block's own "success" is
+/// whichever of `if`/`while`/`until` needs it is the last statement's result.
+pub type Block = Vec<Statement>;
+
+/// A parsed multi-line script statement. `Pipeline` holds the raw
+/// (unexpanded) word list for each `|`-chained simple command rather than a
+/// built `Command`, since loop bodies must re-run `Expander` against the
+/// live `Environment` on every iteration, not once at parse time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Pipeline(Vec<Vec<String>>),
+    If(Block, Block, Option<Block>),
+    While(Block, Block),
+    Until(Block, Block),
+    For(String, Vec<String>, Block),
+    Case(String, Vec<(Vec<String>, Block)>),
+}
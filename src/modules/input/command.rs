@@ -1,19 +1,184 @@
+use std::collections::HashMap;
+
+/// One positional redirection directive parsed from a command line: fd
+/// `fd` is pointed at `target`. A command's redirections are kept in an
+/// ordered list rather than independent fields because shells make order
+/// significant — `>out 2>&1` sends both streams to `out`, while `2>&1
+/// >out` leaves stderr at its original destination and only stdout ends
+/// up in `out`. See `Command::resolve_redirections`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirection {
+    pub fd: u32,
+    pub target: Target,
+}
+
+/// Where a `Redirection` points: a file (for reading, or for writing with
+/// optional append mode), another fd to duplicate — resolved to whatever
+/// that fd holds at the point this redirection is applied, not wherever it
+/// ends up afterward — or literal content to feed in directly (a here-string
+/// or here-doc body). `Literal` is only ever parsed for fd 0, but dup
+/// chasing (`1>&0`) can still carry it onto another fd's slot in `table`;
+/// `resolve_redirections`'s `as_file` only understands `File`, so a
+/// `Literal` reached that way is treated as unredirected rather than
+/// reported as an error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    File {
+        path: String,
+        append: bool,
+        read: bool,
+    },
+    Fd(u32),
+    Literal(String),
+}
+
+/// A resolved redirection on a descriptor other than stdin/stdout/stderr
+/// (fd >= 3) — `N>`, `N>>`, or `N<`. Kept separate from the named
+/// stdin/stdout/stderr fields below since those three have established,
+/// widely-depended-on call sites; higher fds are rarer and open-ended, so
+/// callers that don't care about them can ignore this list entirely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtraRedirection {
+    pub fd: u32,
+    pub path: String,
+    pub append: bool,
+    pub read: bool,
+}
+
+/// Where fd 0's final content comes from after replaying a command's
+/// redirection list: a path to read from disk (`< file`), or literal
+/// content to use as-is (a here-string or here-doc body, already collected
+/// by the time `resolve_redirections` runs).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolvedStdin {
+    File(String),
+    Literal(String),
+}
+
+/// Where fd 0 (stdin), 1 (stdout), and 2 (stderr) ultimately point after
+/// replaying a command's redirection list. `None` means "unredirected" —
+/// whatever that fd's normal destination is. `extra` carries the same
+/// resolution for any other fd referenced in the command, sorted by fd.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ResolvedRedirections {
+    pub stdin: Option<ResolvedStdin>,
+    pub stdout: Option<(String, bool)>,
+    pub stderr: Option<(String, bool)>,
+    /// True when stderr's final value was reached by duplicating fd 1
+    /// (`2>&1`) and that duplication still resolves to the same place
+    /// stdout does — lets a caller share one open file handle between the
+    /// two instead of racing two independent opens of the same path.
+    pub stderr_shares_stdout_handle: bool,
+    pub extra: Vec<ExtraRedirection>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Command {
     pub name: String,
     pub args: Vec<String>,
-    pub stdin: Option<String>,
-    pub stdout: Option<String>,
-    pub append_stdout: bool,
+    pub redirections: Vec<Redirection>,
+    /// Leading `NAME=value` assignments parsed off the front of the command
+    /// line (`FOO=bar BAZ=qux grep pattern file`) — set for the duration of
+    /// this command only. See `CommandProducer::produce_command`.
+    pub env: HashMap<String, String>,
 }
+
 impl Command {
     pub fn new<N: Into<String>>(name: N, args: Vec<String>) -> Self {
         Self {
             name: name.into(),
             args,
-            stdin: None,
-            stdout: None,
-            append_stdout: false,
+            redirections: Vec::new(),
+            env: HashMap::new(),
+        }
+    }
+
+    /// Replays `redirections` left-to-right, resolving each `Target::Fd`
+    /// reference to whatever its target fd pointed to *at that point* —
+    /// not to its eventual final value. This is what makes `>out 2>&1`
+    /// (both streams end up at `out`) and `2>&1 >out` (stderr stays at its
+    /// original destination) resolve differently even though they touch
+    /// the same two fds.
+    pub fn resolve_redirections(&self) -> ResolvedRedirections {
+        let mut table: HashMap<u32, Option<ResolvedTarget>> = HashMap::new();
+        let mut stderr_via_dup = false;
+
+        for redirect in &self.redirections {
+            let resolved = match &redirect.target {
+                Target::File { path, append, read } => Some(ResolvedTarget::File {
+                    path: path.clone(),
+                    append: *append,
+                    read: *read,
+                }),
+                Target::Literal(content) => Some(ResolvedTarget::Literal(content.clone())),
+                Target::Fd(other) => table.get(other).cloned().flatten(),
+            };
+            if redirect.fd == 2 {
+                stderr_via_dup = matches!(redirect.target, Target::Fd(_));
+            }
+            table.insert(redirect.fd, resolved);
+        }
+
+        let as_file = |resolved: Option<ResolvedTarget>| -> Option<(String, bool)> {
+            match resolved {
+                Some(ResolvedTarget::File { path, append, .. }) => Some((path, append)),
+                _ => None,
+            }
+        };
+        let stdout = as_file(table.get(&1).cloned().flatten());
+        let stderr = as_file(table.get(&2).cloned().flatten());
+        let stderr_shares_stdout_handle = stderr_via_dup && stdout.is_some() && stdout == stderr;
+
+        let mut extra: Vec<ExtraRedirection> = table
+            .iter()
+            .filter(|(fd, _)| **fd >= 3)
+            .filter_map(|(fd, resolved)| match resolved {
+                Some(ResolvedTarget::File { path, append, read }) => Some(ExtraRedirection {
+                    fd: *fd,
+                    path: path.clone(),
+                    append: *append,
+                    read: *read,
+                }),
+                _ => None,
+            })
+            .collect();
+        extra.sort_by_key(|redirect| redirect.fd);
+
+        let stdin = table.get(&0).cloned().flatten().map(|resolved| match resolved {
+            ResolvedTarget::File { path, .. } => ResolvedStdin::File(path),
+            ResolvedTarget::Literal(content) => ResolvedStdin::Literal(content),
+        });
+
+        ResolvedRedirections {
+            stdin,
+            stdout,
+            stderr,
+            stderr_shares_stdout_handle,
+            extra,
         }
     }
 }
+
+/// `resolve_redirections`'s internal replay state for one fd: the same
+/// shape as `Target`, minus the `Fd` variant — a `Target::Fd` redirect is
+/// chased immediately to whatever `ResolvedTarget` its target fd held at
+/// that point, so this table never stores an unresolved dup reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ResolvedTarget {
+    File {
+        path: String,
+        append: bool,
+        read: bool,
+    },
+    Literal(String),
+}
+
+/// One `|`-chained group of stages produced by a single call to
+/// `InputProcessor::process`: `stages[0]`'s stdout feeds `stages[1]`'s
+/// stdin, and so on, the way `Runner::execute_pipeline` expects. A line
+/// with no `|` at all is still a `Pipeline` — just one with a single
+/// stage — so callers don't need a separate single-command code path.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Pipeline {
+    pub stages: Vec<Command>,
+}
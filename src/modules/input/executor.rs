@@ -0,0 +1,222 @@
+use crate::modules::command::Command;
+use crate::modules::environment::Environment;
+use crate::modules::input::ast::{Block, Statement};
+use crate::modules::input::errors::{CliError, Result};
+use crate::modules::input::expander::{glob_match, Expander};
+use crate::modules::input::command::ResolvedStdin;
+use crate::modules::input::input_processor::CommandProducer;
+use crate::modules::input::pathname_expander::PathnameExpander;
+use crate::modules::input::quote_handler::QuoteHandler;
+use crate::modules::runner::Runner;
+
+/// Walks a `Parser`-produced `Block`, re-running the tokenize/expand/produce
+/// pipeline against the live `Environment` every time a pipeline executes —
+/// loop bodies must see `$i` and friends change across iterations, so no
+/// step upstream of this can pre-expand them. A pipeline's success (its
+/// last run stage's `Runner::execute_outcome` reporting exit code 0) is the
+/// POSIX "true" that `if`/`while`/`until` conditions test.
+pub struct Executor<'a> {
+    runner: &'a mut Runner,
+    expander: &'a Expander,
+}
+
+impl<'a> Executor<'a> {
+    pub fn new(runner: &'a mut Runner, expander: &'a Expander) -> Self {
+        Self { runner, expander }
+    }
+
+    /// Runs every statement in `block`, returning the last one's success.
+    pub fn run_block(&mut self, env: &mut Environment, block: &Block) -> Result<bool> {
+        let mut success = true;
+        for stmt in block {
+            success = self.run_statement(env, stmt)?;
+        }
+        Ok(success)
+    }
+
+    pub fn run_statement(&mut self, env: &mut Environment, stmt: &Statement) -> Result<bool> {
+        match stmt {
+            Statement::Pipeline(segments) => self.run_pipeline(env, segments),
+            Statement::If(cond, then_block, else_block) => {
+                if self.run_block(env, cond)? {
+                    self.run_block(env, then_block)
+                } else if let Some(else_block) = else_block {
+                    self.run_block(env, else_block)
+                } else {
+                    Ok(true)
+                }
+            }
+            Statement::While(cond, body) => {
+                let mut last = true;
+                while self.run_block(env, cond)? {
+                    last = self.run_block(env, body)?;
+                }
+                Ok(last)
+            }
+            Statement::Until(cond, body) => {
+                let mut last = true;
+                while !self.run_block(env, cond)? {
+                    last = self.run_block(env, body)?;
+                }
+                Ok(last)
+            }
+            Statement::For(var, raw_words, body) => {
+                let words = self.expand_word_list(env, raw_words)?;
+                let mut last = true;
+                for word in words {
+                    env.set(var.clone(), word);
+                    last = self.run_block(env, body)?;
+                }
+                Ok(last)
+            }
+            Statement::Case(raw_word, arms) => {
+                let word = self
+                    .expand_word_list(env, std::slice::from_ref(raw_word))?
+                    .into_iter()
+                    .next()
+                    .unwrap_or_default();
+                for (patterns, body) in arms {
+                    if patterns.iter().any(|pattern| glob_match(pattern, &word)) {
+                        return self.run_block(env, body);
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    /// Expands a raw (possibly quoted) word list through the same
+    /// tokenize/expand/glob pipeline `InputProcessor` uses for one-liners.
+    fn expand_word_list(
+        &mut self,
+        env: &mut Environment,
+        raw_words: &[String],
+    ) -> Result<Vec<String>> {
+        let tokens = QuoteHandler::handle(raw_words)?;
+        let pieces = self.expander.expand_tokens(env, tokens)?;
+        Ok(PathnameExpander::expand(pieces))
+    }
+
+    /// Runs a `|`-chained pipeline, feeding each stage's captured stdout in
+    /// as the next stage's stdin. Stops (failing) at the first stage that
+    /// does not succeed, and the stages after it are not run: a stage whose
+    /// `Runner::execute_outcome` errors outright (e.g. the binary couldn't
+    /// be spawned at all) sets `status` to 1, while a stage that runs but
+    /// exits non-zero sets `status` to its own exit code, the same
+    /// distinction a real shell draws between "command not found" and
+    /// "command ran and failed."
+    fn run_pipeline(&mut self, env: &mut Environment, segments: &[Vec<String>]) -> Result<bool> {
+        let mut stdin_data: Option<String> = None;
+        let mut success = true;
+        for words in segments {
+            let tokens = QuoteHandler::handle(words)?;
+            let pieces = self.expander.expand_tokens(env, tokens)?;
+            let pieces = PathnameExpander::expand(pieces);
+            let parsed = CommandProducer::produce_command(pieces)?;
+
+            // A bare `FOO=bar` segment (empty name) updates the session
+            // environment instead of running anything, the same way
+            // `InputProcessor::process` treats one outside a pipeline.
+            if parsed.name.is_empty() {
+                for (key, value) in parsed.env {
+                    env.set(key, value);
+                }
+                continue;
+            }
+
+            let resolved = parsed.resolve_redirections();
+
+            // `CommandProducer` hands back the input-internal `Command`
+            // (name/args/an ordered redirection list); `Runner::execute_outcome`
+            // wants the full builder-style `Command`, so resolve the
+            // redirections positionally and convert, the same way the REPL
+            // does before running each stage.
+            let mut cmd = Command::new(parsed.name, parsed.args);
+            let piped_stdin = stdin_data.take();
+            let stdin_data_for_stage = if piped_stdin.is_some() {
+                piped_stdin
+            } else {
+                match resolved.stdin {
+                    Some(ResolvedStdin::File(path)) => {
+                        Some(std::fs::read_to_string(&path).map_err(|e| {
+                            CliError::Expansion(format!("failed to read '{path}': {e}"))
+                        })?)
+                    }
+                    Some(ResolvedStdin::Literal(content)) => Some(content),
+                    None => None,
+                }
+            };
+            if let Some(data) = stdin_data_for_stage {
+                cmd = cmd.with_stdin(data);
+            }
+            if let Some((stdout, append)) = resolved.stdout {
+                cmd = cmd.with_stdout(stdout).with_append_stdout(append);
+            }
+            if resolved.stderr_shares_stdout_handle {
+                cmd = cmd.with_stderr_to_stdout();
+            } else if let Some((stderr, append)) = resolved.stderr {
+                cmd = cmd.with_stderr(stderr).with_append_stderr(append);
+            }
+            for extra in resolved.extra {
+                cmd = cmd.with_extra_redirect(extra.fd, extra.path, extra.append, extra.read);
+            }
+            for (key, value) in parsed.env {
+                cmd = cmd.with_env(key, value);
+            }
+
+            match self.runner.execute_outcome(cmd) {
+                Ok(outcome) => {
+                    env.set("status", outcome.code.unwrap_or(1).to_string());
+                    success = outcome.code == Some(0);
+                    stdin_data = Some(outcome.stdout);
+                    if !success {
+                        break;
+                    }
+                }
+                Err(_) => {
+                    env.set("status", "1");
+                    success = false;
+                    break;
+                }
+            }
+        }
+        Ok(success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::input::parser::Parser;
+    use crate::modules::runner::Runner;
+    use std::collections::HashMap;
+
+    /// `run_pipeline` resolves a stage's redirections the same way
+    /// `InputProcessor::pipeline_to_runner_commands` does, independently —
+    /// this exercises that duplicate resolution inside an `if` block's body,
+    /// since nothing else spins up a `Parser`/`Executor` pair over a
+    /// redirection that also uses fd-duplication (`2>&1`).
+    #[test]
+    fn test_run_pipeline_resolves_fd_dup_inside_if_block() {
+        let dir = std::env::temp_dir().join(format!(
+            "cli_rust_executor_test_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let outfile = dir.join("out.txt");
+
+        let script = format!("if echo cond; then echo hi > {} 2>&1; fi", outfile.display());
+        let block = Parser::parse(&script).unwrap();
+
+        let mut runner = Runner::new(dir.clone(), HashMap::new());
+        let expander = Expander::default();
+        let mut executor = Executor::new(&mut runner, &expander);
+        let mut env = Environment::new();
+
+        let success = executor.run_block(&mut env, &block).unwrap();
+        assert!(success);
+        assert_eq!(std::fs::read_to_string(&outfile).unwrap(), "hi\n");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
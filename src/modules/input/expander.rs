@@ -1,47 +1,182 @@
-use regex::Regex;
+use std::rc::Rc;
+
+use users::get_user_by_name;
+use users::os::unix::UserExt;
 
 use crate::modules::environment::Environment;
-use crate::modules::input::errors::Result;
+use crate::modules::input::errors::{CliError, Result};
 use crate::modules::input::token::{Token, TokenMode};
 
-#[derive(Clone)]
+/// Runs a piece of shell text through the crate's own tokenizer/executor
+/// pipeline and returns its captured stdout, or an error message describing
+/// why the inner command failed. Used to service `$(...)` and backtick
+/// command substitution without shelling out to the OS.
+pub type CommandExecutor = dyn Fn(&str) -> std::result::Result<String, String>;
+
+#[derive(Clone, Default)]
 pub struct Expander {
-    re_braced: Regex,
+    executor: Option<Rc<CommandExecutor>>,
 }
 
-impl Default for Expander {
-    fn default() -> Self {
+impl Expander {
+    /// Returns an Expander that services command substitution (`$(...)`,
+    /// `` `...` ``) by running the inner text through `executor`, which
+    /// should parse and execute it via the crate's own pipeline and return
+    /// its captured stdout.
+    pub fn with_executor<F>(executor: F) -> Self
+    where
+        F: Fn(&str) -> std::result::Result<String, String> + 'static,
+    {
         Self {
-            re_braced: Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap(),
+            executor: Some(Rc::new(executor)),
         }
     }
-}
 
-impl Expander {
-    pub fn expand_tokens(&self, env: &Environment, tokens: Vec<Token>) -> Result<Vec<String>> {
-        tokens
-            .into_iter()
-            .map(|t| self.expand_token(env, t))
-            .collect()
+    /// Expands each token's value and pairs it with whether it is still
+    /// eligible for pathname (glob) expansion: only unquoted (`Full`) tokens
+    /// are, since `Raw` and double-quoted (`Weak`) tokens must pass a literal
+    /// `*`/`?`/`[...]` through untouched.
+    pub fn expand_tokens(
+        &self,
+        env: &mut Environment,
+        tokens: Vec<Token>,
+    ) -> Result<Vec<(String, bool)>> {
+        let mut out = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let glob_eligible = matches!(token.mode, TokenMode::Full);
+            // An unquoted word that is *entirely* a `$(...)`/backtick command
+            // substitution gets its captured output word-split on whitespace
+            // into several arguments, same as real shell field splitting —
+            // a substitution embedded in a larger word (`pre$(cmd)post`) does
+            // not, since there's no well-defined way to split the literal
+            // text around it.
+            let splittable =
+                glob_eligible && self.executor.is_some() && is_bare_command_substitution(&token.value);
+            let value = self.expand_token(env, token)?;
+            if splittable {
+                let fields: Vec<(String, bool)> = value
+                    .split_ascii_whitespace()
+                    .map(|field| (field.to_string(), glob_eligible))
+                    .collect();
+                out.extend(fields);
+            } else {
+                out.push((value, glob_eligible));
+            }
+        }
+        Ok(out)
     }
 
-    fn expand_token(&self, env: &Environment, token: Token) -> Result<String> {
+    fn expand_token(&self, env: &mut Environment, token: Token) -> Result<String> {
         Ok(match token.mode {
             TokenMode::Raw => token.value,
-            TokenMode::Weak | TokenMode::Full => self.expand_vars(env, &token.value),
+            TokenMode::Weak | TokenMode::Full => {
+                let tilde_expanded = expand_tilde(env, &token.value);
+                let substituted = self.expand_command_substitutions(env, &tilde_expanded)?;
+                self.expand_vars(env, &substituted)?
+            }
         })
     }
 
-    fn expand_vars(&self, env: &Environment, s: &str) -> String {
+    /// Scans left-to-right for `$((expr))` arithmetic expansion, `$(...)`
+    /// command substitution (tracking paren depth so nested substitutions
+    /// match their own closing paren), and `` `...` `` spans (read to the
+    /// next unescaped backtick, no nesting). A literal `\$(` (protected by
+    /// the `\$` sentinel handling in `expand_vars`) is left untouched here
+    /// since the backslash is only consumed once, in `expand_vars`.
+    fn expand_command_substitutions(&self, env: &mut Environment, s: &str) -> Result<String> {
+        let chars: Vec<char> = s.chars().collect();
+        let mut out = String::with_capacity(s.len());
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] == '\\' && i + 1 < chars.len() && chars[i + 1] == '$' {
+                // Leave the sentinel-protection escape alone for expand_vars.
+                out.push(chars[i]);
+                out.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            if chars[i] == '$'
+                && i + 2 < chars.len()
+                && chars[i + 1] == '('
+                && chars[i + 2] == '('
+            {
+                // `$((` already consumed both opening parens, so the scan
+                // needs to see both of them closed again, not just one.
+                let mut depth = 2;
+                let mut j = i + 3;
+                while j < chars.len() && depth > 0 {
+                    match chars[j] {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                    j += 1;
+                }
+                // `j` now sits just past the matching `))`.
+                let inner: String = chars[i + 3..j.saturating_sub(2)].iter().collect();
+                out.push_str(&evaluate_arithmetic(env, &inner)?.to_string());
+                i = j;
+                continue;
+            }
+            if chars[i] == '$' && i + 1 < chars.len() && chars[i + 1] == '(' {
+                let Some(executor) = &self.executor else {
+                    out.push(chars[i]);
+                    i += 1;
+                    continue;
+                };
+                let mut depth = 1;
+                let mut j = i + 2;
+                while j < chars.len() && depth > 0 {
+                    match chars[j] {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        _ => {}
+                    }
+                    j += 1;
+                }
+                let inner: String = chars[i + 2..j.saturating_sub(1)].iter().collect();
+                let inner = self.expand_command_substitutions(env, &inner)?;
+                let output = executor(&inner).map_err(|e| {
+                    CliError::Expansion(format!("command substitution failed: {e}"))
+                })?;
+                out.push_str(trim_trailing_newline(&output));
+                i = j;
+                continue;
+            }
+            if chars[i] == '`' {
+                let Some(executor) = &self.executor else {
+                    out.push(chars[i]);
+                    i += 1;
+                    continue;
+                };
+                let mut j = i + 1;
+                while j < chars.len() && chars[j] != '`' {
+                    j += 1;
+                }
+                let inner: String = chars[i + 1..j].iter().collect();
+                let output = executor(&inner).map_err(|e| {
+                    CliError::Expansion(format!("command substitution failed: {e}"))
+                })?;
+                out.push_str(trim_trailing_newline(&output));
+                i = j + 1;
+                continue;
+            }
+            out.push(chars[i]);
+            i += 1;
+        }
+        Ok(out)
+    }
+
+    fn expand_vars(&self, env: &mut Environment, s: &str) -> Result<String> {
         // 1) Защитим экранированные `$`: `\$` -> sentinel (удаляем backslash).
-        const S: char = '\u{0001}';
+        const SENTINEL: char = '\u{0001}';
         let mut protected = String::with_capacity(s.len());
         let mut it = s.chars().peekable();
         while let Some(c) = it.next() {
             if c == '\\' {
                 if let Some('$') = it.peek().copied() {
                     let _ = it.next(); // съесть '$'
-                    protected.push(S);
+                    protected.push(SENTINEL);
                     continue;
                 } else {
                     protected.push('\\');
@@ -51,27 +186,69 @@ impl Expander {
             protected.push(c);
         }
 
-        // 2) Подстановки ${VAR} (braced variables first - they are unambiguous)
-        let step = self
-            .re_braced
-            .replace_all(&protected, |caps: &regex::Captures| {
-                env.get(&caps[1]).unwrap_or("").to_string()
-            });
+        // 2) Подстановки ${...} (with operators) and $VAR
+        let chars = protected.chars().collect::<Vec<_>>();
+        let result = self.expand_scan(env, &chars)?;
+
+        // 3) Вернём защищённые '$'
+        Ok(result
+            .chars()
+            .map(|c| if c == SENTINEL { '$' } else { c })
+            .collect())
+    }
 
-        // 3) Подстановки $VAR (simple variables) using longest match strategy
+    /// Scans already `\$`-protected text for `${...}` and `$VAR` spans,
+    /// resolving each through `env`. Used both for the outer token text and
+    /// recursively for the replacement word of a `${VAR:-word}`-style
+    /// operator, since that word may itself contain variable references.
+    fn expand_scan(&self, env: &mut Environment, chars: &[char]) -> Result<String> {
         let mut result = String::new();
-        let chars = step.chars().collect::<Vec<_>>();
         let mut i = 0;
 
         while i < chars.len() {
+            if chars[i] == '$' && i + 1 < chars.len() && chars[i + 1] == '{' {
+                let mut depth = 1;
+                let mut j = i + 2;
+                while j < chars.len() {
+                    match chars[j] {
+                        '{' => depth += 1,
+                        '}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    j += 1;
+                }
+                result.push_str(&self.expand_braced(env, &chars[i + 2..j])?);
+                i = (j + 1).min(chars.len());
+                continue;
+            }
+
             if chars[i] == '$' && i + 1 < chars.len() {
                 let next_char = chars[i + 1];
-                if next_char.is_ascii_alphabetic() || next_char == '_' {
+                if next_char == '?' {
+                    // `$?`/`$status` both resolve to the same `status` var
+                    // that `Executor` writes the last command's exit code
+                    // into — `?` isn't a valid identifier character, so it
+                    // needs its own branch instead of falling into the
+                    // name-scanning one below.
+                    result.push_str(env.get("status").unwrap_or("0"));
+                    i += 2;
+                } else if next_char == '$' {
+                    // `$$`: the shell's own process id, constant for the
+                    // life of this process — unlike `$?` there's nothing in
+                    // `Environment` to read, since no command execution is
+                    // involved.
+                    result.push_str(&std::process::id().to_string());
+                    i += 2;
+                } else if next_char.is_ascii_alphabetic() || next_char == '_' {
                     // Start of a variable name - try to find the longest match
                     let start_pos = i + 1;
                     let mut end_pos = start_pos;
 
-                    // Collect all valid identifier characters
                     while end_pos < chars.len() {
                         let ch = chars[end_pos];
                         if ch.is_ascii_alphanumeric() || ch == '_' {
@@ -86,39 +263,509 @@ impl Expander {
                     for try_end in (start_pos..=end_pos).rev() {
                         let var_name: String = chars[start_pos..try_end].iter().collect();
                         if !var_name.is_empty() && env.get(&var_name).is_some() {
-                            // Found a match!
                             result.push_str(env.get(&var_name).unwrap());
-                            i = try_end; // Continue from after the variable name
+                            i = try_end;
                             matched = true;
                             break;
                         }
                     }
 
                     if !matched {
-                        // No variable found, keep the $ and continue
                         result.push(chars[i]);
                         i += 1;
                     }
                 } else {
-                    // Not a valid variable start, keep the $
                     result.push(chars[i]);
                     i += 1;
                 }
             } else {
-                // Regular character
                 result.push(chars[i]);
                 i += 1;
             }
         }
 
-        // 4) Вернём защищённые '$'
-        result
-            .chars()
-            .map(|c| if c == S { '$' } else { c })
-            .collect()
+        Ok(result)
+    }
+
+    /// Parses the content of `${...}` (name plus an optional POSIX modifier)
+    /// and resolves it against `env`.
+    fn expand_braced(&self, env: &mut Environment, content: &[char]) -> Result<String> {
+        // `${#VAR}` (string length) is its own form, distinct from the
+        // `${VAR#pattern}` prefix-stripping operator below: it's only this
+        // when the `#` is the very first character and everything after it
+        // is a bare identifier with nothing left over.
+        if content.first() == Some(&'#') {
+            let name_chars = &content[1..];
+            let is_bare_name = !name_chars.is_empty()
+                && name_chars
+                    .iter()
+                    .all(|c| c.is_ascii_alphanumeric() || *c == '_');
+            if is_bare_name {
+                let name: String = name_chars.iter().collect();
+                let len = env.get(&name).map(str::len).unwrap_or(0);
+                return Ok(len.to_string());
+            }
+        }
+
+        let mut idx = 0;
+        while idx < content.len() && (content[idx].is_ascii_alphanumeric() || content[idx] == '_')
+        {
+            idx += 1;
+        }
+        let name: String = content[..idx].iter().collect();
+        let rest = &content[idx..];
+
+        if rest.is_empty() {
+            return Ok(env.get(&name).unwrap_or("").to_string());
+        }
+
+        let (op, word, colon): (&str, &[char], bool) = if rest.starts_with(&[':', '-']) {
+            ("-", &rest[2..], true)
+        } else if rest.starts_with(&[':', '=']) {
+            ("=", &rest[2..], true)
+        } else if rest.starts_with(&[':', '+']) {
+            ("+", &rest[2..], true)
+        } else if rest.starts_with(&[':', '?']) {
+            ("?", &rest[2..], true)
+        } else if rest[0] == '-' {
+            ("-", &rest[1..], false)
+        } else if rest[0] == '=' {
+            ("=", &rest[1..], false)
+        } else if rest[0] == '+' {
+            ("+", &rest[1..], false)
+        } else if rest[0] == '?' {
+            ("?", &rest[1..], false)
+        } else if rest.starts_with(&['#', '#']) {
+            ("##", &rest[2..], false)
+        } else if rest[0] == '#' {
+            ("#", &rest[1..], false)
+        } else if rest.starts_with(&['%', '%']) {
+            ("%%", &rest[2..], false)
+        } else if rest[0] == '%' {
+            ("%", &rest[1..], false)
+        } else {
+            // Unrecognized trailing text: fall back to a bare lookup.
+            return Ok(env.get(&name).unwrap_or("").to_string());
+        };
+
+        let current = env.get(&name).map(|v| v.to_string());
+        let is_unset_or_empty = match &current {
+            None => true,
+            Some(v) => colon && v.is_empty(),
+        };
+
+        match op {
+            "-" => {
+                if is_unset_or_empty {
+                    self.expand_scan(env, word)
+                } else {
+                    Ok(current.unwrap_or_default())
+                }
+            }
+            "=" => {
+                if is_unset_or_empty {
+                    let value = self.expand_scan(env, word)?;
+                    env.set(name, value.clone());
+                    Ok(value)
+                } else {
+                    Ok(current.unwrap_or_default())
+                }
+            }
+            "+" => {
+                if is_unset_or_empty {
+                    Ok(String::new())
+                } else {
+                    self.expand_scan(env, word)
+                }
+            }
+            "?" => {
+                if is_unset_or_empty {
+                    let message = self.expand_scan(env, word)?;
+                    Err(CliError::Expansion(message))
+                } else {
+                    Ok(current.unwrap_or_default())
+                }
+            }
+            "#" | "##" => {
+                let value = current.unwrap_or_default();
+                let pattern = self.expand_scan(env, word)?;
+                Ok(strip_glob_prefix(&value, &pattern, op == "##"))
+            }
+            "%" | "%%" => {
+                let value = current.unwrap_or_default();
+                let pattern = self.expand_scan(env, word)?;
+                Ok(strip_glob_suffix(&value, &pattern, op == "%%"))
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Strips the shortest (or longest, if `greedy`) prefix of `value` matching
+/// the glob `pattern` (`*` and `?` wildcards), or returns `value` unchanged
+/// if nothing matches.
+fn strip_glob_prefix(value: &str, pattern: &str, greedy: bool) -> String {
+    let candidates: Vec<usize> = (0..=value.len())
+        .filter(|&end| value.is_char_boundary(end) && glob_match(pattern, &value[..end]))
+        .collect();
+    let chosen = if greedy {
+        candidates.into_iter().max()
+    } else {
+        candidates.into_iter().min()
+    };
+    match chosen {
+        Some(end) => value[end..].to_string(),
+        None => value.to_string(),
     }
 }
 
+/// Strips the shortest (or longest, if `greedy`) suffix of `value` matching
+/// the glob `pattern`, or returns `value` unchanged if nothing matches.
+fn strip_glob_suffix(value: &str, pattern: &str, greedy: bool) -> String {
+    let candidates: Vec<usize> = (0..=value.len())
+        .filter(|&start| value.is_char_boundary(start) && glob_match(pattern, &value[start..]))
+        .collect();
+    let chosen = if greedy {
+        candidates.into_iter().min()
+    } else {
+        candidates.into_iter().max()
+    };
+    match chosen {
+        Some(start) => value[..start].to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Minimal glob matcher supporting `*` (any run of characters) and `?` (any
+/// single character), sufficient for `${VAR#pat}`-style trimming.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    fn go(p: &[char], t: &[char]) -> bool {
+        match p.first() {
+            None => t.is_empty(),
+            Some('*') => go(&p[1..], t) || (!t.is_empty() && go(p, &t[1..])),
+            Some('?') => !t.is_empty() && go(&p[1..], &t[1..]),
+            Some(c) => t.first() == Some(c) && go(&p[1..], &t[1..]),
+        }
+    }
+    go(&p, &t)
+}
+
+/// Evaluates a `$((...))` arithmetic expression over `i64`, resolving bare
+/// identifiers and `$VAR` references through `env` (unset -> 0).
+fn evaluate_arithmetic(env: &Environment, expr: &str) -> Result<i64> {
+    let tokens = tokenize_arithmetic(expr)?;
+    let mut parser = ArithParser {
+        tokens: &tokens,
+        pos: 0,
+        env,
+    };
+    let value = parser.parse_or()?;
+    if parser.pos != parser.tokens.len() {
+        return Err(CliError::Expansion(format!(
+            "arithmetic syntax error near token {}",
+            parser.pos
+        )));
+    }
+    Ok(value)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ArithToken {
+    Number(i64),
+    Ident(String),
+    Op(String),
+    LParen,
+    RParen,
+}
+
+fn tokenize_arithmetic(expr: &str) -> Result<Vec<ArithToken>> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push(ArithToken::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(ArithToken::RParen);
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            tokens.push(ArithToken::Number(text.parse().map_err(|_| {
+                CliError::Expansion(format!("invalid number '{text}' in arithmetic expression"))
+            })?));
+        } else if c == '$' || c.is_ascii_alphabetic() || c == '_' {
+            let start = if c == '$' { i + 1 } else { i };
+            let mut j = start;
+            while j < chars.len() && (chars[j].is_ascii_alphanumeric() || chars[j] == '_') {
+                j += 1;
+            }
+            let name: String = chars[start..j].iter().collect();
+            tokens.push(ArithToken::Ident(name));
+            i = j;
+        } else {
+            // Greedily match the longest known multi-char operator first.
+            let rest: String = chars[i..].iter().collect();
+            let op = ["&&", "||", "==", "!=", "<=", ">="]
+                .into_iter()
+                .find(|op| rest.starts_with(op))
+                .map(|op| op.to_string())
+                .unwrap_or_else(|| c.to_string());
+            i += op.chars().count();
+            tokens.push(ArithToken::Op(op));
+        }
+    }
+    Ok(tokens)
+}
+
+struct ArithParser<'a> {
+    tokens: &'a [ArithToken],
+    pos: usize,
+    env: &'a Environment,
+}
+
+impl<'a> ArithParser<'a> {
+    fn peek(&self) -> Option<&ArithToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn is_op(&self, op: &str) -> bool {
+        matches!(self.peek(), Some(ArithToken::Op(o)) if o == op)
+    }
+
+    fn bump(&mut self) {
+        self.pos += 1;
+    }
+
+    // Precedence, loosest to tightest: `||` < `&&` < comparison < add/sub < mul/div/mod < unary < primary.
+    fn parse_or(&mut self) -> Result<i64> {
+        let mut left = self.parse_and()?;
+        while self.is_op("||") {
+            self.bump();
+            let right = self.parse_and()?;
+            left = ((left != 0) || (right != 0)) as i64;
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<i64> {
+        let mut left = self.parse_comparison()?;
+        while self.is_op("&&") {
+            self.bump();
+            let right = self.parse_comparison()?;
+            left = ((left != 0) && (right != 0)) as i64;
+        }
+        Ok(left)
+    }
+
+    fn parse_comparison(&mut self) -> Result<i64> {
+        let mut left = self.parse_additive()?;
+        loop {
+            let op = match self.peek() {
+                Some(ArithToken::Op(o))
+                    if ["<", "<=", ">", ">=", "==", "!="].contains(&o.as_str()) =>
+                {
+                    o.clone()
+                }
+                _ => break,
+            };
+            self.bump();
+            let right = self.parse_additive()?;
+            left = match op.as_str() {
+                "<" => (left < right) as i64,
+                "<=" => (left <= right) as i64,
+                ">" => (left > right) as i64,
+                ">=" => (left >= right) as i64,
+                "==" => (left == right) as i64,
+                "!=" => (left != right) as i64,
+                _ => unreachable!(),
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> Result<i64> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Some(ArithToken::Op(o)) if o == "+" || o == "-" => o.clone(),
+                _ => break,
+            };
+            self.bump();
+            let right = self.parse_multiplicative()?;
+            left = if op == "+" { left + right } else { left - right };
+        }
+        Ok(left)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<i64> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(ArithToken::Op(o)) if o == "*" || o == "/" || o == "%" => o.clone(),
+                _ => break,
+            };
+            self.bump();
+            let right = self.parse_unary()?;
+            left = match op.as_str() {
+                "*" => left * right,
+                "/" => {
+                    if right == 0 {
+                        return Err(CliError::Expansion("division by zero".to_string()));
+                    }
+                    left / right
+                }
+                "%" => {
+                    if right == 0 {
+                        return Err(CliError::Expansion("modulo by zero".to_string()));
+                    }
+                    left % right
+                }
+                _ => unreachable!(),
+            };
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<i64> {
+        if self.is_op("-") {
+            self.bump();
+            return Ok(-self.parse_unary()?);
+        }
+        if self.is_op("+") {
+            self.bump();
+            return self.parse_unary();
+        }
+        if self.is_op("!") {
+            self.bump();
+            return Ok((self.parse_unary()? == 0) as i64);
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<i64> {
+        match self.peek().cloned() {
+            Some(ArithToken::Number(n)) => {
+                self.bump();
+                Ok(n)
+            }
+            Some(ArithToken::Ident(name)) => {
+                self.bump();
+                Ok(self.env.get(&name).and_then(|v| v.parse().ok()).unwrap_or(0))
+            }
+            Some(ArithToken::LParen) => {
+                self.bump();
+                let value = self.parse_or()?;
+                if !matches!(self.peek(), Some(ArithToken::RParen)) {
+                    return Err(CliError::Expansion("unbalanced parentheses".to_string()));
+                }
+                self.bump();
+                Ok(value)
+            }
+            _ => Err(CliError::Expansion(
+                "unexpected end of arithmetic expression".to_string(),
+            )),
+        }
+    }
+}
+
+/// Expands POSIX tilde-prefixes in `s`: a bare `~` or `~/...` at the start of
+/// a tilde-prefix resolves via `$HOME`, and `~name/...` resolves to `name`'s
+/// home directory via the system user database. A tilde-prefix starts the
+/// word, or follows an unquoted `:` or the assignment `=` in strings like
+/// `PATH=~/bin:~/x`; a `~` anywhere else (e.g. `a~b`) is left alone. Failed
+/// lookups (unset `$HOME`, unknown user) leave the prefix untouched.
+fn expand_tilde(env: &Environment, s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut out = String::with_capacity(s.len());
+    let mut i = 0;
+    let mut at_prefix_start = true;
+    let mut seen_assignment_eq = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if at_prefix_start && c == '~' {
+            let start = i + 1;
+            let mut j = start;
+            while j < chars.len() && chars[j] != '/' && chars[j] != ':' {
+                j += 1;
+            }
+            let name: String = chars[start..j].iter().collect();
+            match resolve_tilde_user(env, &name) {
+                Some(home) => out.push_str(&home),
+                None => {
+                    out.push('~');
+                    out.push_str(&name);
+                }
+            }
+            i = j;
+            at_prefix_start = false;
+            continue;
+        }
+
+        at_prefix_start = c == ':' || (c == '=' && !seen_assignment_eq);
+        seen_assignment_eq = seen_assignment_eq || c == '=';
+        out.push(c);
+        i += 1;
+    }
+
+    out
+}
+
+/// Resolves the home directory for a tilde-prefix's user name, or `$HOME`
+/// when `name` is empty (a bare `~` or `~/...`).
+fn resolve_tilde_user(env: &Environment, name: &str) -> Option<String> {
+    if name.is_empty() {
+        env.get("HOME").map(|s| s.to_string())
+    } else {
+        get_user_by_name(name)?
+            .home_dir()
+            .to_str()
+            .map(|s| s.to_string())
+    }
+}
+
+/// Strips a single trailing newline (or CRLF), per POSIX command substitution.
+fn trim_trailing_newline(s: &str) -> &str {
+    s.strip_suffix('\n')
+        .map(|s| s.strip_suffix('\r').unwrap_or(s))
+        .unwrap_or(s)
+}
+
+/// True when `s` is, in its entirety, a single `$(...)` or `` `...` `` span
+/// with no other literal text around it — the case `expand_tokens` word-
+/// splits the substitution's output for, since a substitution embedded in a
+/// larger word has no well-defined split point.
+fn is_bare_command_substitution(s: &str) -> bool {
+    let chars: Vec<char> = s.chars().collect();
+    if chars.len() >= 2 && chars[0] == '`' && chars[chars.len() - 1] == '`' {
+        return chars[1..chars.len() - 1].iter().all(|&c| c != '`');
+    }
+    if chars.len() >= 3 && chars[0] == '$' && chars[1] == '(' && chars.get(2) != Some(&'(') {
+        let mut depth = 1;
+        let mut j = 2;
+        while j < chars.len() && depth > 0 {
+            match chars[j] {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            j += 1;
+        }
+        return depth == 0 && j == chars.len();
+    }
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -133,8 +780,8 @@ mod tests {
 
         let exp = Expander::default();
         let tokens = vec![Token::new("$x$y", TokenMode::Full)];
-        let res = exp.expand_tokens(&env, tokens).expect("expand failed");
-        assert_eq!(res, vec!["exit".to_string()]);
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("exit".to_string(), true)]);
     }
 
     #[test]
@@ -146,13 +793,13 @@ mod tests {
         let exp = Expander::default();
         // Test the case: pre$A$Bp should expand to pre12p, not pre1 (where $Bp is treated as undefined variable)
         let tokens = vec![Token::new("pre$A$Bp", TokenMode::Full)];
-        let res = exp.expand_tokens(&env, tokens).expect("expand failed");
-        assert_eq!(res, vec!["pre12p".to_string()]);
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("pre12p".to_string(), true)]);
 
         // Also test braced variables to ensure deterministic behavior
         let tokens = vec![Token::new("pre${A}${B}post", TokenMode::Full)];
-        let res = exp.expand_tokens(&env, tokens).expect("expand failed");
-        assert_eq!(res, vec!["pre12post".to_string()]);
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("pre12post".to_string(), true)]);
     }
 
     #[test]
@@ -162,7 +809,357 @@ mod tests {
 
         let exp = Expander::default();
         let tokens = vec![Token::new("$VARtext", TokenMode::Full)];
-        let res = exp.expand_tokens(&env, tokens).expect("expand failed");
-        assert_eq!(res, vec!["valuetext".to_string()]);
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("valuetext".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_command_substitution_dollar_paren() {
+        let mut env = Environment::new();
+        let exp = Expander::with_executor(|cmd| Ok(format!("ran[{cmd}]\n")));
+
+        let tokens = vec![Token::new("before $(echo x) after", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("before ran[echo x] after".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_command_substitution_backtick() {
+        let mut env = Environment::new();
+        let exp = Expander::with_executor(|cmd| Ok(format!("ran[{cmd}]\n")));
+
+        // A bare, whole-token substitution word-splits its captured output
+        // on whitespace, same as real shell field splitting.
+        let tokens = vec![Token::new("`echo x`", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(
+            res,
+            vec![("ran[echo".to_string(), true), ("x]".to_string(), true)]
+        );
+    }
+
+    #[test]
+    fn test_command_substitution_nested() {
+        let mut env = Environment::new();
+        let exp = Expander::with_executor(|cmd| Ok(format!("[{cmd}]\n")));
+
+        // A bare, whole-token substitution word-splits its captured output
+        // on whitespace, same as real shell field splitting.
+        let tokens = vec![Token::new("$(echo $(echo x))", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(
+            res,
+            vec![
+                ("[echo".to_string(), true),
+                ("[echo".to_string(), true),
+                ("x]]".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_substitution_failure_reports_expansion_error() {
+        let mut env = Environment::new();
+        let exp = Expander::with_executor(|_cmd| Err("exit status 1".to_string()));
+
+        let tokens = vec![Token::new("$(false)", TokenMode::Full)];
+        let err = exp.expand_tokens(&mut env, tokens).unwrap_err();
+        assert_eq!(
+            err,
+            CliError::Expansion("command substitution failed: exit status 1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_command_substitution_bare_word_splits_on_whitespace() {
+        let mut env = Environment::new();
+        let exp = Expander::with_executor(|cmd| Ok(format!("{cmd} a b\n")));
+
+        let tokens = vec![Token::new("$(cat file.txt)", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(
+            res,
+            vec![
+                ("cat".to_string(), true),
+                ("file.txt".to_string(), true),
+                ("a".to_string(), true),
+                ("b".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_substitution_embedded_in_word_is_not_split() {
+        let mut env = Environment::new();
+        let exp = Expander::with_executor(|_cmd| Ok("a b".to_string()));
+
+        let tokens = vec![Token::new("pre$(echo x)post", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("prea bpost".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_command_substitution_raw_token_untouched() {
+        let mut env = Environment::new();
+        let exp = Expander::with_executor(|_cmd| Ok("should not run".to_string()));
+
+        let tokens = vec![Token::new("$(echo x)", TokenMode::Raw)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("$(echo x)".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_command_substitution_escaped_dollar() {
+        let mut env = Environment::new();
+        let exp = Expander::with_executor(|_cmd| Ok("should not run".to_string()));
+
+        let tokens = vec![Token::new(r"\$(echo x)", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("$(echo x)".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_default_value_colon_dash() {
+        let mut env = Environment::new();
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("${UNSET:-fallback}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("fallback".to_string(), true)]);
+
+        env.set("EMPTY", "");
+        let tokens = vec![Token::new("${EMPTY:-fallback}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("fallback".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_default_value_dash_unset_only() {
+        let mut env = Environment::new();
+        env.set("EMPTY", "");
+        let exp = Expander::default();
+
+        // `-word` (no colon) only kicks in when the variable is unset, not merely empty.
+        let tokens = vec![Token::new("${EMPTY-fallback}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_assign_default_colon_equals() {
+        let mut env = Environment::new();
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("${VAR:=assigned}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("assigned".to_string(), true)]);
+        assert_eq!(env.get("VAR"), Some("assigned"));
+    }
+
+    #[test]
+    fn test_use_alternate_colon_plus() {
+        let mut env = Environment::new();
+        env.set("VAR", "set");
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("${VAR:+alt}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("alt".to_string(), true)]);
+
+        let tokens = vec![Token::new("${UNSET:+alt}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_error_if_unset_colon_question() {
+        let mut env = Environment::new();
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("${UNSET:?missing value}", TokenMode::Full)];
+        let err = exp.expand_tokens(&mut env, tokens).unwrap_err();
+        assert_eq!(err, CliError::Expansion("missing value".to_string()));
+    }
+
+    #[test]
+    fn test_hash_var_expands_to_string_length() {
+        let mut env = Environment::new();
+        env.set("VAR", "hello");
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("${#VAR}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("5".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_hash_var_is_zero_when_unset() {
+        let mut env = Environment::new();
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("${#UNSET}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("0".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_trim_prefix_and_suffix() {
+        let mut env = Environment::new();
+        env.set("PATH_LIKE", "aaabbb");
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("${PATH_LIKE#a}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("aabbb".to_string(), true)]);
+
+        // `##` is greedy: for "aaabbb", the glob `a*b` matches the whole
+        // string as its longest prefix, leaving nothing behind.
+        let tokens = vec![Token::new("${PATH_LIKE##a*b}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("".to_string(), true)]);
+
+        let tokens = vec![Token::new("${PATH_LIKE%b}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("aaabb".to_string(), true)]);
+
+        let tokens = vec![Token::new("${PATH_LIKE%%b*b}", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("aaa".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_arithmetic_basic() {
+        let mut env = Environment::new();
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("$((1 + 2 * 3))", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("7".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_arithmetic_with_variables_and_comparison() {
+        let mut env = Environment::new();
+        env.set("X", "5");
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("$(($X > 3 && $X < 10))", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("1".to_string(), true)]);
+
+        let tokens = vec![Token::new("$((UNSET + 1))", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("1".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_arithmetic_division_by_zero_errors() {
+        let mut env = Environment::new();
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("$((1 / 0))", TokenMode::Full)];
+        let err = exp.expand_tokens(&mut env, tokens).unwrap_err();
+        assert_eq!(err, CliError::Expansion("division by zero".to_string()));
+    }
+
+    #[test]
+    fn test_tilde_expansion_bare_and_path() {
+        let mut env = Environment::new();
+        env.set("HOME", "/home/bob");
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("~", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("/home/bob".to_string(), true)]);
+
+        let tokens = vec![Token::new("~/docs", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("/home/bob/docs".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_tilde_expansion_in_assignment_list() {
+        let mut env = Environment::new();
+        env.set("HOME", "/home/bob");
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("PATH=~/bin:~/x", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("PATH=/home/bob/bin:/home/bob/x".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_tilde_mid_word_untouched() {
+        let mut env = Environment::new();
+        env.set("HOME", "/home/bob");
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("a~b", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("a~b".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_tilde_unresolvable_user_left_unchanged() {
+        let mut env = Environment::new();
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("~no_such_user_xyz/path", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("~no_such_user_xyz/path".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_dollar_question_expands_status_var() {
+        let mut env = Environment::new();
+        env.set("status", "1");
+
+        let exp = Expander::default();
+        let tokens = vec![Token::new("exit code: $?", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("exit code: 1".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_dollar_status_and_dollar_question_read_the_same_value() {
+        let mut env = Environment::new();
+        env.set("status", "42");
+
+        let exp = Expander::default();
+        let tokens = vec![Token::new("$status/$?", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("42/42".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_dollar_question_defaults_to_zero_when_unset() {
+        let mut env = Environment::new();
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("$?", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("0".to_string(), true)]);
+    }
+
+    #[test]
+    fn test_dollar_question_stays_literal_in_single_quotes() {
+        let mut env = Environment::new();
+        env.set("status", "1");
+
+        let exp = Expander::default();
+        let tokens = vec![Token::new("$?", TokenMode::Raw)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        assert_eq!(res, vec![("$?".to_string(), false)]);
+    }
+
+    #[test]
+    fn test_dollar_dollar_expands_to_process_id() {
+        let mut env = Environment::new();
+        let exp = Expander::default();
+
+        let tokens = vec![Token::new("pid:$$", TokenMode::Full)];
+        let res = exp.expand_tokens(&mut env, tokens).expect("expand failed");
+        let expected = format!("pid:{}", std::process::id());
+        assert_eq!(res, vec![(expected, true)]);
     }
 }
@@ -1,12 +1,39 @@
+use std::collections::{HashMap, HashSet};
+
 use crate::modules::input::{
-    command::Command,
+    command::{Command, Pipeline, Redirection, Target},
     errors::{CliError, Result},
     expander::Expander,
+    pathname_expander::PathnameExpander,
     quote_handler::QuoteHandler,
+    token::{Token, TokenMode},
     tokenizer::Tokenizer,
     Environment,
 };
 
+/// A redirection operator recognized by `parse_redirect_op`, before the
+/// filename (if any) that follows it in the token stream is consumed.
+enum RedirectOp {
+    /// `<`/`0<`/`N<`, `>`/`1>`/`N>` and their `>>` append forms — consumes
+    /// the following token as a filename. `read` is true for `<`-style
+    /// operators (open for reading) and false for `>`/`>>`-style ones.
+    ToFile { fd: u32, append: bool, read: bool },
+    /// `N>&M` (e.g. `2>&1`) — duplicates fd `M` into fd `fd`. Consumes no
+    /// following token.
+    ToFd { fd: u32, target_fd: u32 },
+    /// `&>`/`&>>` — routes both stdout and stderr to the single filename
+    /// that follows.
+    ToFileBoth { append: bool },
+    /// `<<<` — a here-string; the following token is fed to fd 0 verbatim
+    /// (plus a trailing newline) rather than treated as a filename.
+    HereString,
+    /// `<<`/`<<-` — a here-doc; the following token is the closing
+    /// delimiter (consumed here, matched by the REPL's continuation-reading
+    /// mode), and the collected body is supplied separately since it isn't
+    /// part of this token stream.
+    HereDoc,
+}
+
 /// CommandProducer is responsible for converting parsed tokens into Command objects
 /// with proper redirection handling (stdin, stdout, stderr).
 pub struct CommandProducer;
@@ -14,95 +41,293 @@ pub struct CommandProducer;
 impl CommandProducer {
     /// Produces a Command object from tokenized command pieces, handling redirection operators.
     ///
-    /// This method parses redirection operators like `>`, `>>`, `2>`, `<` etc. and builds
-    /// a Command with appropriate stdin, stdout, and stderr configurations.
-    pub fn produce_command(mut pieces: Vec<String>) -> Result<Command> {
+    /// This method parses redirection operators like `>`, `>>`, `2>`, `<`, `2>&1`, `&>` etc.
+    /// and builds a Command carrying them as an ordered `Vec<Redirection>`, so later stages
+    /// can replay them positionally via `Command::resolve_redirections`. Leading `NAME=value`
+    /// tokens (`FOO=bar BAZ=qux cmd args`) are split off first and recorded on `Command::env`
+    /// rather than treated as the command name or an argument; if every token turns out to be
+    /// an assignment, the returned `Command` has an empty `name` — callers (`InputProcessor::
+    /// process`, `Executor::run_pipeline`) treat that as "apply `env` to the session
+    /// environment instead of running anything", the same way a bare `FOO=bar` behaves in a
+    /// real shell.
+    pub fn produce_command(pieces: Vec<String>) -> Result<Command> {
+        Self::produce_command_with_heredoc(pieces, None)
+    }
+
+    /// Same as `produce_command`, but resolves a `<<DELIM` here-doc operator
+    /// against `heredoc_body` — the text the REPL's continuation-reading
+    /// mode collected between the line containing `<<DELIM` and the closing
+    /// delimiter — instead of rejecting it outright. `<<<` here-strings
+    /// never need this since their content is already the following token;
+    /// `heredoc_body` is consumed (taken) by the first `<<` encountered, so
+    /// a second one on the same line has nothing left to resolve against.
+    pub fn produce_command_with_heredoc(
+        mut pieces: Vec<String>,
+        mut heredoc_body: Option<String>,
+    ) -> Result<Command> {
         if pieces.is_empty() {
             return Err(CliError::EmptyCommand);
         }
+        let env = Self::take_leading_assignments(&mut pieces);
+        if pieces.is_empty() {
+            let mut cmd = Command::new(String::new(), Vec::new());
+            cmd.env = env;
+            return Ok(cmd);
+        }
         let name = pieces.remove(0);
         let mut args = Vec::<String>::new();
-        let mut stdin = None;
-        let mut stdout = None;
-        let mut append_stdout = false;
-        let mut stderr = None;
-        let mut append_stderr = false;
+        let mut redirections = Vec::new();
 
         let mut it = pieces.into_iter().peekable();
         while let Some(p) = it.next() {
-            match p.as_str() {
-                "<" | "0<" => stdin = it.next(),
-                ">" | "1>" => {
-                    append_stdout = false;
-                    stdout = it.next();
-                }
-                ">>" | "1>>" => {
-                    append_stdout = true;
-                    stdout = it.next();
+            match parse_redirect_op(&p) {
+                Some(RedirectOp::ToFile { fd, append, read }) => {
+                    if let Some(path) = it.next() {
+                        redirections.push(Redirection {
+                            fd,
+                            target: Target::File {
+                                path,
+                                append,
+                                read,
+                            },
+                        });
+                    }
                 }
-                "2>" => {
-                    append_stderr = false;
-                    stderr = it.next();
+                Some(RedirectOp::ToFd { fd, target_fd }) => {
+                    redirections.push(Redirection {
+                        fd,
+                        target: Target::Fd(target_fd),
+                    });
                 }
-                "2>>" => {
-                    append_stderr = true;
-                    stderr = it.next();
+                Some(RedirectOp::ToFileBoth { append }) => {
+                    if let Some(path) = it.next() {
+                        redirections.push(Redirection {
+                            fd: 1,
+                            target: Target::File {
+                                path,
+                                append,
+                                read: false,
+                            },
+                        });
+                        redirections.push(Redirection {
+                            fd: 2,
+                            target: Target::Fd(1),
+                        });
+                    }
                 }
-                _ => {
-                    // Check for patterns like "3>", "4>>", etc.
-                    if let Some(fd_redirect) = parse_fd_redirect(&p) {
-                        let target_file = it.next();
-                        match fd_redirect {
-                            (0, false) => stdin = target_file, // "0>"  (unusual but possible)
-                            (1, false) => {
-                                append_stdout = false;
-                                stdout = target_file;
-                            } // "1>"
-                            (1, true) => {
-                                append_stdout = true;
-                                stdout = target_file;
-                            } // "1>>"
-                            (2, false) => {
-                                append_stderr = false;
-                                stderr = target_file;
-                            } // "2>"
-                            (2, true) => {
-                                append_stderr = true;
-                                stderr = target_file;
-                            } // "2>>"
-                            _ => {
-                                // For fd >= 3, we could extend Command struct to support them
-                                // For now, ignore or add to args
-                                args.push(p);
-                                if let Some(file) = target_file {
-                                    args.push(file);
-                                }
-                            }
-                        }
-                    } else {
-                        args.push(p);
+                Some(RedirectOp::HereString) => {
+                    if let Some(content) = it.next() {
+                        redirections.push(Redirection {
+                            fd: 0,
+                            target: Target::Literal(format!("{content}\n")),
+                        });
                     }
                 }
+                Some(RedirectOp::HereDoc) => {
+                    let _delimiter = it.next();
+                    let body = heredoc_body.take().ok_or_else(|| {
+                        CliError::Tokenization(
+                            "<< used with no here-doc body available".to_string(),
+                        )
+                    })?;
+                    redirections.push(Redirection {
+                        fd: 0,
+                        target: Target::Literal(body),
+                    });
+                }
+                None => args.push(p),
             }
         }
         let mut cmd = Command::new(name, args);
-        cmd.stdin = stdin;
-        cmd.stdout = stdout;
-        cmd.append_stdout = append_stdout;
-        cmd.stderr = stderr;
-        cmd.append_stderr = append_stderr;
+        cmd.redirections = redirections;
+        cmd.env = env;
         Ok(cmd)
     }
+
+    /// Strips `NAME=value` tokens off the front of `pieces`, stopping at the
+    /// first token that isn't one (which becomes the command name). Mirrors
+    /// POSIX's `FOO=bar BAZ=qux cmd args` inline-assignment syntax, where
+    /// only *leading* assignments count — `cmd FOO=bar` treats `FOO=bar` as
+    /// a plain argument instead.
+    fn take_leading_assignments(pieces: &mut Vec<String>) -> HashMap<String, String> {
+        let mut env = HashMap::new();
+        while let Some(first) = pieces.first() {
+            match parse_assignment(first) {
+                Some((key, value)) => {
+                    env.insert(key, value);
+                    pieces.remove(0);
+                }
+                None => break,
+            }
+        }
+        env
+    }
+}
+
+/// Parses `s` as a shell-style `NAME=value` assignment: `NAME` must be a
+/// non-empty run of ASCII letters, digits, and underscores not starting
+/// with a digit, followed by `=`. Returns `None` for anything else (a plain
+/// argument, a path containing `=`, etc.).
+fn parse_assignment(s: &str) -> Option<(String, String)> {
+    let (name, value) = s.split_once('=')?;
+    if name.is_empty() {
+        return None;
+    }
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    if !(first.is_ascii_alphabetic() || first == '_') {
+        return None;
+    }
+    if !chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some((name.to_string(), value.to_string()))
+}
+
+/// Strips one layer of matching leading/trailing `'`/`"` quotes from an
+/// assignment's value, e.g. the `'wc -l'` left over from `ll='wc -l'`:
+/// `QuoteHandler` only unquotes a token when the quotes span it entirely,
+/// so a quoted value fused to its `name=` by `=` (no surrounding quotes of
+/// its own) reaches here with the quote characters still attached.
+fn strip_assignment_value_quotes(value: &str) -> &str {
+    let bytes = value.as_bytes();
+    if bytes.len() >= 2 {
+        let (first, last) = (bytes[0], bytes[bytes.len() - 1]);
+        if first == last && (first == b'\'' || first == b'"') {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
 }
 
-pub struct InputProcessorBuilder {}
+/// Recognizes a single redirection operator token: `<`/`0<`/`N<`,
+/// `>`/`1>`/`N>` (and their `>>` append forms), `&>`/`&>>`, `N>&M`
+/// fd-duplication (`2>&1`, `1>&2`), and the here-doc/here-string stdin forms
+/// `<<`/`<<-`/`<<<`. Returns `None` for anything else, which the caller
+/// treats as a plain argument.
+fn parse_redirect_op(s: &str) -> Option<RedirectOp> {
+    match s {
+        "<<<" => return Some(RedirectOp::HereString),
+        "<<" | "<<-" => return Some(RedirectOp::HereDoc),
+        "<" | "0<" => {
+            return Some(RedirectOp::ToFile {
+                fd: 0,
+                append: false,
+                read: true,
+            })
+        }
+        ">" | "1>" => {
+            return Some(RedirectOp::ToFile {
+                fd: 1,
+                append: false,
+                read: false,
+            })
+        }
+        ">>" | "1>>" => {
+            return Some(RedirectOp::ToFile {
+                fd: 1,
+                append: true,
+                read: false,
+            })
+        }
+        "2>" => {
+            return Some(RedirectOp::ToFile {
+                fd: 2,
+                append: false,
+                read: false,
+            })
+        }
+        "2>>" => {
+            return Some(RedirectOp::ToFile {
+                fd: 2,
+                append: true,
+                read: false,
+            })
+        }
+        "&>" => return Some(RedirectOp::ToFileBoth { append: false }),
+        "&>>" => return Some(RedirectOp::ToFileBoth { append: true }),
+        _ => {}
+    }
+
+    if let Some((fd, target_fd)) = parse_fd_dup(s) {
+        return Some(RedirectOp::ToFd { fd, target_fd });
+    }
+
+    parse_fd_redirect(s).map(|(fd, append, read)| RedirectOp::ToFile { fd, append, read })
+}
+
+/// Parses `N>&M` fd-duplication tokens like `2>&1` or `1>&2` into
+/// `(fd, target_fd)`.
+fn parse_fd_dup(s: &str) -> Option<(u32, u32)> {
+    let (fd_part, target_part) = s.split_once(">&")?;
+    let fd = fd_part.parse().ok()?;
+    let target_fd = target_part.parse().ok()?;
+    Some((fd, target_fd))
+}
+
+/// Expands a leading alias reference, like the alias step in MOROS's shell:
+/// only the command-name position (`tokens[0]`) is ever checked, and only
+/// when it's an unquoted (`TokenMode::Full`) word — POSIX alias rules don't
+/// expand a quoted name or anything past the first word. If it names a
+/// defined alias, the alias's value is tokenized the same way the rest of
+/// the line was and spliced in place of that one token; the new first token
+/// is then checked again, so `alias ll=la` + `alias la='ls -la'` lets `ll`
+/// expand transitively. An alias that (directly or through a chain) would
+/// expand into itself is left alone rather than recursing forever.
+fn expand_aliases(env: &Environment, mut tokens: Vec<Token>) -> Result<Vec<Token>> {
+    let mut already_expanded = HashSet::new();
+
+    loop {
+        let Some(first) = tokens.first() else {
+            break;
+        };
+        if first.mode != TokenMode::Full || already_expanded.contains(&first.value) {
+            break;
+        }
+        let Some(value) = env.get_alias(&first.value) else {
+            break;
+        };
+
+        already_expanded.insert(first.value.clone());
+        let replacement_raw = Tokenizer::tokenize(value)?;
+        let mut replacement = QuoteHandler::handle(&replacement_raw)?;
+
+        tokens.remove(0);
+        replacement.append(&mut tokens);
+        tokens = replacement;
+    }
+
+    Ok(tokens)
+}
+
+pub struct InputProcessorBuilder {
+    expander: Expander,
+}
 impl InputProcessorBuilder {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            expander: Expander::default(),
+        }
     }
+
+    /// Wires `$(...)`/backtick command substitution to `executor` instead of
+    /// leaving it untouched — `executor` should parse and run its argument
+    /// through the crate's own pipeline (e.g. a `Runner`) and return its
+    /// captured stdout, or an error message if the inner command failed.
+    /// Takes a plain closure rather than a `Runner` directly so this module
+    /// doesn't need to depend on `runner`'s concrete type; a caller (the
+    /// REPL) builds the closure around its own `Runner` handle.
+    pub fn with_executor<F>(mut self, executor: F) -> Self
+    where
+        F: Fn(&str) -> std::result::Result<String, String> + 'static,
+    {
+        self.expander = Expander::with_executor(executor);
+        self
+    }
+
     pub fn build(self) -> InputProcessor {
         InputProcessor {
-            expander: Expander::default(),
+            expander: self.expander,
         }
     }
 }
@@ -121,7 +346,40 @@ pub struct InputProcessor {
 }
 
 impl InputProcessor {
-    pub fn process(&self, line: &str, env_vars: &Environment) -> Result<Vec<Command>> {
+    /// The `Expander` this processor expands one-liners with — `Executor`
+    /// borrows it to expand words inside a compound command's body with the
+    /// exact same substitution/expansion rules a plain pipeline gets.
+    pub(crate) fn expander(&self) -> &Expander {
+        &self.expander
+    }
+
+    /// Parses one input line into a single `Pipeline` — its stages are the
+    /// unquoted-`|`-separated parts of the line, in order, each still
+    /// carrying its own redirections (`Command::resolve_redirections`).
+    /// A line with no `|` comes back as a one-stage `Pipeline`. Bare
+    /// `FOO=bar` assignments and `alias`/`unalias` invocations are applied
+    /// to `env_vars` directly rather than becoming stages, the same way
+    /// they're excluded from the old flat command list. The one exception is
+    /// a bare `alias` with no arguments, which becomes an `echo` stage
+    /// listing the defined aliases (or no stage when none is defined).
+    pub fn process(&self, line: &str, env_vars: &mut Environment) -> Result<Pipeline> {
+        self.process_with_heredoc(line, env_vars, Vec::new())
+    }
+
+    /// Same as `process`, but resolves each `<<DELIM` here-doc operator in
+    /// `line` against the next entry of `heredoc_bodies` instead of erroring
+    /// — the REPL collects these itself, one per operator in the order they
+    /// appear (left to right, so across `|`-chained stages too), by reading
+    /// and concatenating lines until one matches that operator's delimiter,
+    /// before calling this. A plain `process` call is just this with an
+    /// empty `heredoc_bodies`, for callers (most of them, and every existing
+    /// test) that never produce one.
+    pub fn process_with_heredoc(
+        &self,
+        line: &str,
+        env_vars: &mut Environment,
+        heredoc_bodies: Vec<String>,
+    ) -> Result<Pipeline> {
         // 1) Токенизируем всю строку (учитывая кавычки/экраны)
         let raw = Tokenizer::tokenize(line)?;
 
@@ -129,29 +387,133 @@ impl InputProcessor {
         let parts = split_on_pipes_tokens(&raw);
 
         // 3) Обрабатываем каждую часть отдельной командой
-        let mut cmds = Vec::with_capacity(parts.len());
+        let mut stages = Vec::with_capacity(parts.len());
+        let mut heredoc_bodies: std::collections::VecDeque<String> =
+            heredoc_bodies.into_iter().collect();
         for raw_part in parts {
             let tokens = QuoteHandler::handle(&raw_part)?;
+            let tokens = expand_aliases(env_vars, tokens)?;
             let pieces = self.expander.expand_tokens(env_vars, tokens)?;
-            cmds.push(CommandProducer::produce_command(pieces)?);
+            let pieces = PathnameExpander::expand(pieces);
+            // Each stage's own `<<`/`<<-` operator (if any) consumes the
+            // next not-yet-claimed body, in line order — taking one
+            // unconditionally on every stage would starve a later stage
+            // that has its own operator, and handing the same content to
+            // every stage would silently duplicate it.
+            let has_heredoc_op = pieces.iter().any(|p| p == "<<" || p == "<<-");
+            let body_for_stage = if has_heredoc_op {
+                heredoc_bodies.pop_front()
+            } else {
+                None
+            };
+            let cmd = CommandProducer::produce_command_with_heredoc(pieces, body_for_stage)?;
+            // A bare `FOO=bar` with no following command name (an empty
+            // `cmd.name`) updates the session environment directly instead
+            // of being queued as something to run — the same way a bare
+            // assignment behaves in a real shell.
+            if cmd.name.is_empty() {
+                for (key, value) in cmd.env {
+                    env_vars.set(key, value);
+                }
+                continue;
+            }
+            // `alias`/`unalias` edit the session's alias table directly,
+            // the same way a bare assignment edits `env_vars` above,
+            // instead of being queued as something to run. `alias` with no
+            // `name=value` arguments lists every defined alias instead,
+            // using the same `name order` the `BTreeMap` in `Environment`
+            // already keeps for exactly this case; it's spliced in as an
+            // `echo` stage so it still flows out through the pipeline like
+            // any other command's output.
+            if cmd.name == "alias" {
+                if cmd.args.is_empty() {
+                    let defined: Vec<String> = env_vars
+                        .aliases()
+                        .map(|(name, value)| format!("alias {name}={value}"))
+                        .collect();
+                    if !defined.is_empty() {
+                        stages.push(Command::new("echo".to_string(), vec![defined.join("\n")]));
+                    }
+                    continue;
+                }
+                for arg in &cmd.args {
+                    if let Some((name, value)) = arg.split_once('=') {
+                        let value = strip_assignment_value_quotes(value);
+                        env_vars.set_alias(name.to_string(), value.to_string());
+                    }
+                }
+                continue;
+            }
+            if cmd.name == "unalias" {
+                for arg in &cmd.args {
+                    env_vars.remove_alias(arg);
+                }
+                continue;
+            }
+            stages.push(cmd);
         }
-        Ok(cmds)
+        Ok(Pipeline { stages })
     }
 }
 
-/// Parse file descriptor redirection patterns like "2>", "3>>", "1>", etc.
-/// Returns Some((fd_number, is_append)) if the string matches a pattern, None otherwise.
-fn parse_fd_redirect(s: &str) -> Option<(u32, bool)> {
-    if s.ends_with(">>") {
-        if let Some(fd_part) = s.strip_suffix(">>") {
-            if let Ok(fd) = fd_part.parse::<u32>() {
-                return Some((fd, true)); // append mode
+/// Converts a parsed `Pipeline` into the `Command` list `Runner::
+/// execute_pipeline` wants: each stage's positional redirections are
+/// resolved and translated into the builder-style fields `Runner` reads,
+/// the same mapping `Executor::run_pipeline` uses for the block grammar.
+/// The first stage's `< file` is read eagerly into `stdin` since `Runner`'s
+/// `Command` carries stdin as already-loaded content, not a path.
+pub fn pipeline_to_runner_commands(
+    pipeline: Pipeline,
+) -> std::io::Result<Vec<crate::modules::command::Command>> {
+    let mut commands = Vec::with_capacity(pipeline.stages.len());
+    for stage in pipeline.stages {
+        let resolved = stage.resolve_redirections();
+        let mut cmd = crate::modules::command::Command::new(stage.name, stage.args);
+        match resolved.stdin {
+            Some(crate::modules::input::command::ResolvedStdin::File(path)) => {
+                cmd = cmd.with_stdin(std::fs::read_to_string(path)?);
+            }
+            Some(crate::modules::input::command::ResolvedStdin::Literal(content)) => {
+                cmd = cmd.with_stdin(content);
             }
+            None => {}
+        }
+        if let Some((stdout, append)) = resolved.stdout {
+            cmd = cmd.with_stdout(stdout).with_append_stdout(append);
+        }
+        if resolved.stderr_shares_stdout_handle {
+            cmd = cmd.with_stderr_to_stdout();
+        } else if let Some((stderr, append)) = resolved.stderr {
+            cmd = cmd.with_stderr(stderr).with_append_stderr(append);
+        }
+        for extra in resolved.extra {
+            cmd = cmd.with_extra_redirect(extra.fd, extra.path, extra.append, extra.read);
+        }
+        for (key, value) in stage.env {
+            cmd = cmd.with_env(key, value);
+        }
+        commands.push(cmd);
+    }
+    Ok(commands)
+}
+
+/// Parse file descriptor redirection patterns like "2>", "3>>", "10<", etc.
+/// Returns Some((fd_number, is_append, is_read)) if the string matches a
+/// pattern, None otherwise.
+fn parse_fd_redirect(s: &str) -> Option<(u32, bool, bool)> {
+    if let Some(fd_part) = s.strip_suffix(">>") {
+        if let Ok(fd) = fd_part.parse::<u32>() {
+            return Some((fd, true, false)); // append mode
         }
     } else if let Some(fd_part) = s.strip_suffix('>') {
-        // Pattern like "2>" or "1>"
+        // Pattern like "2>" or "1>" or "3>"
+        if let Ok(fd) = fd_part.parse::<u32>() {
+            return Some((fd, false, false)); // overwrite mode
+        }
+    } else if let Some(fd_part) = s.strip_suffix('<') {
+        // Pattern like "3<" or "10<"
         if let Ok(fd) = fd_part.parse::<u32>() {
-            return Some((fd, false)); // overwrite mode
+            return Some((fd, false, true)); // read mode
         }
     }
     None
@@ -174,3 +536,470 @@ fn split_on_pipes_tokens(raw: &[String]) -> Vec<Vec<String>> {
     }
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::input::command::ExtraRedirection;
+
+    fn pieces(words: &[&str]) -> Vec<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_produce_command_plain_redirections() {
+        let cmd = CommandProducer::produce_command(pieces(&[
+            "grep", "pattern", "<", "in.txt", ">", "out.txt", "2>", "err.txt",
+        ]))
+        .unwrap();
+        let resolved = cmd.resolve_redirections();
+
+        assert_eq!(cmd.name, "grep");
+        assert_eq!(cmd.args, vec!["pattern"]);
+        assert_eq!(
+            resolved.stdin,
+            Some(crate::modules::input::command::ResolvedStdin::File(
+                "in.txt".to_string()
+            ))
+        );
+        assert_eq!(resolved.stdout, Some(("out.txt".to_string(), false)));
+        assert_eq!(resolved.stderr, Some(("err.txt".to_string(), false)));
+        assert!(!resolved.stderr_shares_stdout_handle);
+    }
+
+    #[test]
+    fn test_produce_command_append_forms() {
+        let cmd =
+            CommandProducer::produce_command(pieces(&["cmd", ">>", "out.log", "2>>", "err.log"]))
+                .unwrap();
+        let resolved = cmd.resolve_redirections();
+
+        assert_eq!(resolved.stdout, Some(("out.log".to_string(), true)));
+        assert_eq!(resolved.stderr, Some(("err.log".to_string(), true)));
+    }
+
+    #[test]
+    fn test_produce_command_ampersand_redirect() {
+        let cmd = CommandProducer::produce_command(pieces(&["cmd", "&>", "both.log"])).unwrap();
+        let resolved = cmd.resolve_redirections();
+
+        assert_eq!(resolved.stdout, Some(("both.log".to_string(), false)));
+        assert_eq!(resolved.stderr, Some(("both.log".to_string(), false)));
+        assert!(resolved.stderr_shares_stdout_handle);
+    }
+
+    #[test]
+    fn test_produce_command_ampersand_append_redirect() {
+        let cmd = CommandProducer::produce_command(pieces(&["cmd", "&>>", "both.log"])).unwrap();
+        let resolved = cmd.resolve_redirections();
+
+        assert_eq!(resolved.stdout, Some(("both.log".to_string(), true)));
+        assert_eq!(resolved.stderr, Some(("both.log".to_string(), true)));
+        assert!(resolved.stderr_shares_stdout_handle);
+    }
+
+    #[test]
+    fn test_produce_command_stdout_then_dup_merges() {
+        // `>out 2>&1` — stderr is duped to fd 1 *after* it already points
+        // at `out`, so both streams end up at the same file.
+        let cmd =
+            CommandProducer::produce_command(pieces(&["cmd", ">", "out.txt", "2>&1"])).unwrap();
+        let resolved = cmd.resolve_redirections();
+
+        assert_eq!(resolved.stdout, Some(("out.txt".to_string(), false)));
+        assert_eq!(resolved.stderr, Some(("out.txt".to_string(), false)));
+        assert!(resolved.stderr_shares_stdout_handle);
+    }
+
+    #[test]
+    fn test_produce_command_dup_then_stdout_does_not_merge() {
+        // `2>&1 >out` — stderr dups fd 1 *before* it gets redirected, so it
+        // keeps its original (unredirected) destination while only stdout
+        // moves to `out`.
+        let cmd =
+            CommandProducer::produce_command(pieces(&["cmd", "2>&1", ">", "out.txt"])).unwrap();
+        let resolved = cmd.resolve_redirections();
+
+        assert_eq!(resolved.stdout, Some(("out.txt".to_string(), false)));
+        assert_eq!(resolved.stderr, None);
+        assert!(!resolved.stderr_shares_stdout_handle);
+    }
+
+    #[test]
+    fn test_produce_command_reverse_dup() {
+        // `1>&2` — stdout dups fd 2, which is itself unredirected here.
+        let cmd = CommandProducer::produce_command(pieces(&["cmd", "1>&2"])).unwrap();
+        let resolved = cmd.resolve_redirections();
+
+        assert_eq!(resolved.stdout, None);
+        assert_eq!(resolved.stderr, None);
+    }
+
+    #[test]
+    fn test_produce_command_high_fd_redirect_resolves() {
+        let cmd = CommandProducer::produce_command(pieces(&[
+            "cmd",
+            "3>",
+            "file3.txt",
+            "4>>",
+            "file4.log",
+            "10>",
+            "file10.txt",
+        ]))
+        .unwrap();
+        let resolved = cmd.resolve_redirections();
+
+        assert!(cmd.args.is_empty());
+        assert_eq!(
+            resolved.extra,
+            vec![
+                ExtraRedirection {
+                    fd: 3,
+                    path: "file3.txt".to_string(),
+                    append: false,
+                    read: false,
+                },
+                ExtraRedirection {
+                    fd: 4,
+                    path: "file4.log".to_string(),
+                    append: true,
+                    read: false,
+                },
+                ExtraRedirection {
+                    fd: 10,
+                    path: "file10.txt".to_string(),
+                    append: false,
+                    read: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_produce_command_high_fd_read_redirect() {
+        let cmd = CommandProducer::produce_command(pieces(&["cmd", "3<", "input3.txt"])).unwrap();
+        let resolved = cmd.resolve_redirections();
+
+        assert!(cmd.args.is_empty());
+        assert_eq!(
+            resolved.extra,
+            vec![ExtraRedirection {
+                fd: 3,
+                path: "input3.txt".to_string(),
+                append: false,
+                read: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_produce_command_leading_assignments_are_stripped() {
+        let cmd = CommandProducer::produce_command(pieces(&[
+            "FOO=bar", "BAZ=qux", "grep", "pattern", "file",
+        ]))
+        .unwrap();
+
+        assert_eq!(cmd.name, "grep");
+        assert_eq!(cmd.args, vec!["pattern", "file"]);
+        assert_eq!(cmd.env.get("FOO"), Some(&"bar".to_string()));
+        assert_eq!(cmd.env.get("BAZ"), Some(&"qux".to_string()));
+    }
+
+    #[test]
+    fn test_produce_command_assignment_after_name_is_an_argument() {
+        let cmd = CommandProducer::produce_command(pieces(&["echo", "FOO=bar"])).unwrap();
+
+        assert_eq!(cmd.name, "echo");
+        assert_eq!(cmd.args, vec!["FOO=bar"]);
+        assert!(cmd.env.is_empty());
+    }
+
+    #[test]
+    fn test_produce_command_bare_assignment_has_empty_name() {
+        let cmd = CommandProducer::produce_command(pieces(&["FOO=bar"])).unwrap();
+
+        assert!(cmd.name.is_empty());
+        assert!(cmd.args.is_empty());
+        assert_eq!(cmd.env.get("FOO"), Some(&"bar".to_string()));
+    }
+
+    #[test]
+    fn test_process_bare_assignment_updates_session_environment() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+
+        let pipeline = processor.process("FOO=bar", &mut env).unwrap();
+
+        assert!(pipeline.stages.is_empty());
+        assert_eq!(env.get("FOO"), Some("bar"));
+    }
+
+    #[test]
+    fn test_expand_aliases_splices_defined_alias() {
+        let mut env = Environment::new();
+        env.set_alias("ll", "ls -la");
+
+        let tokens = vec![Token::new("ll", TokenMode::Full), Token::new("/tmp", TokenMode::Full)];
+        let expanded = expand_aliases(&env, tokens).unwrap();
+
+        let values: Vec<&str> = expanded.iter().map(|t| t.value.as_str()).collect();
+        assert_eq!(values, vec!["ls", "-la", "/tmp"]);
+    }
+
+    #[test]
+    fn test_expand_aliases_chains_through_multiple_aliases() {
+        let mut env = Environment::new();
+        env.set_alias("ll", "la -h");
+        env.set_alias("la", "ls -a");
+
+        let tokens = vec![Token::new("ll", TokenMode::Full)];
+        let expanded = expand_aliases(&env, tokens).unwrap();
+
+        let values: Vec<&str> = expanded.iter().map(|t| t.value.as_str()).collect();
+        assert_eq!(values, vec!["ls", "-a", "-h"]);
+    }
+
+    #[test]
+    fn test_expand_aliases_guards_against_cycles() {
+        let mut env = Environment::new();
+        env.set_alias("a", "b");
+        env.set_alias("b", "a");
+
+        let tokens = vec![Token::new("a", TokenMode::Full)];
+        let expanded = expand_aliases(&env, tokens).unwrap();
+
+        // Stops as soon as a name it already expanded would recur, instead
+        // of looping forever.
+        let values: Vec<&str> = expanded.iter().map(|t| t.value.as_str()).collect();
+        assert_eq!(values, vec!["a"]);
+    }
+
+    #[test]
+    fn test_expand_aliases_ignores_quoted_command_name() {
+        let mut env = Environment::new();
+        env.set_alias("ll", "ls -la");
+
+        let tokens = vec![Token::new("ll", TokenMode::Raw)];
+        let expanded = expand_aliases(&env, tokens).unwrap();
+
+        let values: Vec<&str> = expanded.iter().map(|t| t.value.as_str()).collect();
+        assert_eq!(values, vec!["ll"]);
+    }
+
+    #[test]
+    fn test_process_alias_defines_alias_for_later_commands() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+
+        let pipeline = processor.process("alias ll=ls", &mut env).unwrap();
+        assert!(pipeline.stages.is_empty());
+        assert_eq!(env.get_alias("ll"), Some("ls"));
+
+        let pipeline = processor.process("ll", &mut env).unwrap();
+        assert_eq!(pipeline.stages.len(), 1);
+        assert_eq!(pipeline.stages[0].name, "ls");
+    }
+
+    #[test]
+    fn test_process_alias_with_no_args_lists_defined_aliases() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+        env.set_alias("ll", "ls -la");
+        env.set_alias("la", "ls -a");
+
+        let pipeline = processor.process("alias", &mut env).unwrap();
+
+        assert_eq!(pipeline.stages.len(), 1);
+        assert_eq!(pipeline.stages[0].name, "echo");
+        assert_eq!(pipeline.stages[0].args, vec!["alias la=ls -a\nalias ll=ls -la"]);
+    }
+
+    #[test]
+    fn test_process_alias_with_no_args_and_none_defined_produces_no_stage() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+
+        let pipeline = processor.process("alias", &mut env).unwrap();
+
+        assert!(pipeline.stages.is_empty());
+    }
+
+    #[test]
+    fn test_process_unalias_removes_alias() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+        env.set_alias("ll", "ls -la");
+
+        let pipeline = processor.process("unalias ll", &mut env).unwrap();
+        assert!(pipeline.stages.is_empty());
+        assert_eq!(env.get_alias("ll"), None);
+
+        let pipeline = processor.process("ll", &mut env).unwrap();
+        assert_eq!(pipeline.stages.len(), 1);
+        assert_eq!(pipeline.stages[0].name, "ll");
+    }
+
+    #[test]
+    fn test_process_splits_pipe_chain_into_pipeline_stages() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+
+        let pipeline = processor.process("echo hi | grep h | wc -l", &mut env).unwrap();
+
+        assert_eq!(pipeline.stages.len(), 3);
+        assert_eq!(pipeline.stages[0].name, "echo");
+        assert_eq!(pipeline.stages[1].name, "grep");
+        assert_eq!(pipeline.stages[2].name, "wc");
+    }
+
+    #[test]
+    fn test_process_expands_alias_in_a_non_first_pipeline_stage() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+        env.set_alias("count", "wc -l");
+
+        let pipeline = processor.process("echo hi | count", &mut env).unwrap();
+
+        assert_eq!(pipeline.stages.len(), 2);
+        assert_eq!(pipeline.stages[0].name, "echo");
+        assert_eq!(pipeline.stages[1].name, "wc");
+        assert_eq!(pipeline.stages[1].args, vec!["-l"]);
+    }
+
+    #[test]
+    fn test_process_ignores_quoted_alias_head_in_a_pipeline_stage() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+        env.set_alias("count", "wc -l");
+
+        let pipeline = processor.process(r#"echo hi | "count""#, &mut env).unwrap();
+
+        assert_eq!(pipeline.stages.len(), 2);
+        assert_eq!(pipeline.stages[1].name, "count");
+        assert!(pipeline.stages[1].args.is_empty());
+    }
+
+    #[test]
+    fn test_process_empty_pipeline_stage_is_an_error() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+
+        let err = processor.process("echo x | | cat", &mut env).unwrap_err();
+
+        assert!(matches!(err, CliError::EmptyCommand));
+    }
+
+    #[test]
+    fn test_pipeline_to_runner_commands_reads_first_stage_stdin_file() {
+        let dir = std::env::temp_dir().join(format!(
+            "cli_rust_pipeline_test_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let infile = dir.join("in.txt");
+        std::fs::write(&infile, "hello\n").unwrap();
+
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+        let pipeline = processor
+            .process(&format!("cat < {} | wc -l", infile.display()), &mut env)
+            .unwrap();
+
+        let commands = pipeline_to_runner_commands(pipeline).unwrap();
+
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].stdin, Some("hello\n".to_string()));
+        assert_eq!(commands[1].name, "wc");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_produce_command_here_string_feeds_literal_with_trailing_newline() {
+        let cmd = CommandProducer::produce_command(pieces(&["cat", "<<<", "hello"])).unwrap();
+        let resolved = cmd.resolve_redirections();
+
+        assert_eq!(
+            resolved.stdin,
+            Some(crate::modules::input::command::ResolvedStdin::Literal(
+                "hello\n".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_produce_command_here_doc_resolves_against_supplied_body() {
+        let cmd = CommandProducer::produce_command_with_heredoc(
+            pieces(&["cat", "<<", "EOF"]),
+            Some("line one\nline two\n".to_string()),
+        )
+        .unwrap();
+        let resolved = cmd.resolve_redirections();
+
+        assert_eq!(
+            resolved.stdin,
+            Some(crate::modules::input::command::ResolvedStdin::Literal(
+                "line one\nline two\n".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_produce_command_here_doc_without_body_is_an_error() {
+        let err =
+            CommandProducer::produce_command_with_heredoc(pieces(&["cat", "<<", "EOF"]), None)
+                .unwrap_err();
+
+        assert!(matches!(err, CliError::Tokenization(_)));
+    }
+
+    #[test]
+    fn test_process_with_heredoc_threads_body_into_pipeline_stage() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+
+        let pipeline = processor
+            .process_with_heredoc(
+                "cat << EOF",
+                &mut env,
+                vec!["from the heredoc\n".to_string()],
+            )
+            .unwrap();
+
+        let commands = pipeline_to_runner_commands(pipeline).unwrap();
+
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].stdin, Some("from the heredoc\n".to_string()));
+    }
+
+    #[test]
+    fn test_pipeline_to_runner_commands_reads_here_string_literal() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+        let pipeline = processor.process("cat <<< hi", &mut env).unwrap();
+
+        let commands = pipeline_to_runner_commands(pipeline).unwrap();
+
+        assert_eq!(commands[0].stdin, Some("hi\n".to_string()));
+    }
+
+    #[test]
+    fn test_process_with_heredoc_assigns_each_pipeline_stage_its_own_body_in_order() {
+        let processor = InputProcessorBuilder::new().build();
+        let mut env = Environment::new();
+
+        let pipeline = processor
+            .process_with_heredoc(
+                "cat << A | cat << B",
+                &mut env,
+                vec!["first\n".to_string(), "second\n".to_string()],
+            )
+            .unwrap();
+
+        let commands = pipeline_to_runner_commands(pipeline).unwrap();
+
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].stdin, Some("first\n".to_string()));
+        assert_eq!(commands[1].stdin, Some("second\n".to_string()));
+    }
+}
@@ -1,11 +1,17 @@
+pub mod ast;
 pub mod command;
 pub mod errors;
+pub mod executor;
 pub mod expander;
 pub mod input_processor;
+pub mod parser;
+pub mod pathname_expander;
 pub mod quote_handler;
 pub mod token;
 pub mod tokenizer;
 
 pub use crate::modules::environment::Environment;
+pub use executor::Executor;
 pub use input_processor::{CommandProducer, InputProcessor, InputProcessorBuilder};
+pub use parser::Parser;
 pub use token::{Token, TokenMode};
@@ -0,0 +1,429 @@
+use crate::modules::input::ast::{Block, Statement};
+use crate::modules::input::errors::{CliError, Result};
+
+/// One lexical unit of a control-flow script: a shell word (quotes left
+/// intact, for `QuoteHandler` to interpret later), a single statement
+/// separator (`;` or newline), or a `;;` case-arm terminator.
+#[derive(Debug, Clone, PartialEq)]
+enum Lexeme {
+    Word(String),
+    Terminator,
+    CaseEnd,
+}
+
+/// Hand-rolled recursive-descent parser for POSIX compound commands
+/// (`if`/`while`/`until`/`for`/`case`) over a flat stream of [`Lexeme`]s.
+/// Plain commands are left as raw word lists; `InputProcessor`'s usual
+/// tokenize/expand/produce pipeline still turns those into `Command`s, just
+/// deferred to execution time (see `Executor`).
+pub struct Parser<'a> {
+    lexemes: &'a [Lexeme],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Parses a whole script (possibly spanning many lines) into a `Block`.
+    pub fn parse(script: &str) -> Result<Block> {
+        let lexemes = lex(script)?;
+        let mut parser = Parser {
+            lexemes: &lexemes,
+            pos: 0,
+        };
+        let block = parser.parse_block(&[])?;
+        if parser.pos != parser.lexemes.len() {
+            return Err(CliError::Tokenization(format!(
+                "unexpected token near position {}",
+                parser.pos
+            )));
+        }
+        Ok(block)
+    }
+
+    fn peek_word(&self) -> Option<&str> {
+        match self.lexemes.get(self.pos) {
+            Some(Lexeme::Word(w)) => Some(w.as_str()),
+            _ => None,
+        }
+    }
+
+    fn at_keyword(&self, kw: &str) -> bool {
+        self.peek_word() == Some(kw)
+    }
+
+    fn bump(&mut self) {
+        self.pos += 1;
+    }
+
+    fn skip_terminators(&mut self) {
+        while matches!(self.lexemes.get(self.pos), Some(Lexeme::Terminator)) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect_keyword(&mut self, kw: &str) -> Result<()> {
+        if self.at_keyword(kw) {
+            self.bump();
+            Ok(())
+        } else {
+            Err(CliError::Tokenization(format!(
+                "expected '{kw}', found {:?}",
+                self.lexemes.get(self.pos)
+            )))
+        }
+    }
+
+    /// Parses statements until EOF, a `CaseEnd` (`;;`), or a word matching
+    /// one of `enders` — whichever comes first is left unconsumed so the
+    /// caller can match on it.
+    fn parse_block(&mut self, enders: &[&str]) -> Result<Block> {
+        let mut block = Vec::new();
+        self.skip_terminators();
+        while self.pos < self.lexemes.len() {
+            if matches!(self.lexemes[self.pos], Lexeme::CaseEnd) {
+                break;
+            }
+            if let Some(w) = self.peek_word() {
+                if enders.contains(&w) {
+                    break;
+                }
+            }
+            block.push(self.parse_statement()?);
+            self.skip_terminators();
+        }
+        Ok(block)
+    }
+
+    fn parse_statement(&mut self) -> Result<Statement> {
+        match self.peek_word() {
+            Some("if") => self.parse_if(),
+            Some("while") => self.parse_while_until(false),
+            Some("until") => self.parse_while_until(true),
+            Some("for") => self.parse_for(),
+            Some("case") => self.parse_case(),
+            _ => self.parse_pipeline(),
+        }
+    }
+
+    fn parse_if(&mut self) -> Result<Statement> {
+        self.expect_keyword("if")?;
+        let stmt = self.parse_if_chain()?;
+        self.expect_keyword("fi")?;
+        Ok(stmt)
+    }
+
+    /// Parses the `COND then BLOCK` shared by `if` and each `elif`, chaining
+    /// further `elif`/`else` clauses into nested `If`s in the `else` branch.
+    /// Never consumes the closing `fi` — only the outermost `parse_if` does.
+    fn parse_if_chain(&mut self) -> Result<Statement> {
+        let cond = self.parse_block(&["then"])?;
+        self.expect_keyword("then")?;
+        let then_block = self.parse_block(&["elif", "else", "fi"])?;
+        let else_block = if self.at_keyword("elif") {
+            self.bump();
+            Some(vec![self.parse_if_chain()?])
+        } else if self.at_keyword("else") {
+            self.bump();
+            Some(self.parse_block(&["fi"])?)
+        } else {
+            None
+        };
+        Ok(Statement::If(cond, then_block, else_block))
+    }
+
+    fn parse_while_until(&mut self, until: bool) -> Result<Statement> {
+        self.expect_keyword(if until { "until" } else { "while" })?;
+        let cond = self.parse_block(&["do"])?;
+        self.expect_keyword("do")?;
+        let body = self.parse_block(&["done"])?;
+        self.expect_keyword("done")?;
+        Ok(if until {
+            Statement::Until(cond, body)
+        } else {
+            Statement::While(cond, body)
+        })
+    }
+
+    fn parse_for(&mut self) -> Result<Statement> {
+        self.expect_keyword("for")?;
+        let var = self
+            .peek_word()
+            .ok_or_else(|| CliError::Tokenization("expected loop variable after 'for'".into()))?
+            .to_string();
+        self.bump();
+        self.expect_keyword("in")?;
+
+        let mut words = Vec::new();
+        while let Some(w) = self.peek_word() {
+            words.push(w.to_string());
+            self.bump();
+        }
+        self.skip_terminators();
+        self.expect_keyword("do")?;
+        let body = self.parse_block(&["done"])?;
+        self.expect_keyword("done")?;
+        Ok(Statement::For(var, words, body))
+    }
+
+    fn parse_case(&mut self) -> Result<Statement> {
+        self.expect_keyword("case")?;
+        let word = self
+            .peek_word()
+            .ok_or_else(|| CliError::Tokenization("expected word after 'case'".into()))?
+            .to_string();
+        self.bump();
+        self.expect_keyword("in")?;
+        self.skip_terminators();
+
+        let mut arms = Vec::new();
+        while !self.at_keyword("esac") {
+            if self.pos >= self.lexemes.len() {
+                return Err(CliError::Tokenization(
+                    "unterminated 'case': missing 'esac'".into(),
+                ));
+            }
+            let patterns = self.parse_case_patterns()?;
+            let body = self.parse_block(&["esac"])?;
+            if matches!(self.lexemes.get(self.pos), Some(Lexeme::CaseEnd)) {
+                self.bump();
+            }
+            self.skip_terminators();
+            arms.push((patterns, body));
+        }
+        self.expect_keyword("esac")?;
+        Ok(Statement::Case(word, arms))
+    }
+
+    /// Reads a `pattern(|pattern)*)` case-arm header, splitting on `|`
+    /// whether it arrived glued to the words (`a|b)`) or as its own
+    /// whitespace-separated token (`a | b)`).
+    fn parse_case_patterns(&mut self) -> Result<Vec<String>> {
+        let mut patterns = Vec::new();
+        let mut current = String::new();
+        loop {
+            let word = self
+                .peek_word()
+                .ok_or_else(|| CliError::Tokenization("expected case pattern and ')'".into()))?
+                .to_string();
+            self.bump();
+
+            let mut rest = word.as_str();
+            loop {
+                match rest.find(['|', ')']) {
+                    Some(idx) => {
+                        current.push_str(&rest[..idx]);
+                        let sep = rest.as_bytes()[idx] as char;
+                        patterns.push(std::mem::take(&mut current));
+                        rest = &rest[idx + 1..];
+                        if sep == ')' {
+                            return Ok(patterns);
+                        }
+                        if rest.is_empty() {
+                            break;
+                        }
+                    }
+                    None => {
+                        current.push_str(rest);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    fn parse_pipeline(&mut self) -> Result<Statement> {
+        let mut segments: Vec<Vec<String>> = vec![Vec::new()];
+        loop {
+            match self.lexemes.get(self.pos) {
+                Some(Lexeme::Word(w)) if w == "|" => {
+                    self.bump();
+                    segments.push(Vec::new());
+                }
+                Some(Lexeme::Word(w)) => {
+                    segments
+                        .last_mut()
+                        .expect("segments always non-empty")
+                        .push(w.clone());
+                    self.bump();
+                }
+                _ => break,
+            }
+        }
+        if segments.iter().all(Vec::is_empty) {
+            return Err(CliError::EmptyCommand);
+        }
+        Ok(Statement::Pipeline(segments))
+    }
+}
+
+/// Splits `script` into words (quote-aware, quotes left intact) and
+/// statement separators. `;;` is distinguished from a single `;` so `case`
+/// arms can be told apart from ordinary statement boundaries.
+fn lex(script: &str) -> Result<Vec<Lexeme>> {
+    let chars: Vec<char> = script.chars().collect();
+    let mut lexemes = Vec::new();
+    let mut current = String::new();
+    let mut i = 0;
+
+    macro_rules! flush_word {
+        () => {
+            if !current.is_empty() {
+                lexemes.push(Lexeme::Word(std::mem::take(&mut current)));
+            }
+        };
+    }
+
+    while i < chars.len() {
+        match chars[i] {
+            '\'' => {
+                current.push('\'');
+                i += 1;
+                while i < chars.len() && chars[i] != '\'' {
+                    current.push(chars[i]);
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err(CliError::Tokenization("unterminated single quote".into()));
+                }
+                current.push('\'');
+                i += 1;
+            }
+            '"' => {
+                current.push('"');
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' && i + 1 < chars.len() {
+                        current.push(chars[i]);
+                        current.push(chars[i + 1]);
+                        i += 2;
+                        continue;
+                    }
+                    current.push(chars[i]);
+                    i += 1;
+                }
+                if i >= chars.len() {
+                    return Err(CliError::Tokenization("unterminated double quote".into()));
+                }
+                current.push('"');
+                i += 1;
+            }
+            ' ' | '\t' => {
+                flush_word!();
+                i += 1;
+            }
+            '\r' => {
+                i += 1;
+            }
+            '\n' => {
+                flush_word!();
+                lexemes.push(Lexeme::Terminator);
+                i += 1;
+            }
+            ';' => {
+                flush_word!();
+                if i + 1 < chars.len() && chars[i + 1] == ';' {
+                    lexemes.push(Lexeme::CaseEnd);
+                    i += 2;
+                } else {
+                    lexemes.push(Lexeme::Terminator);
+                    i += 1;
+                }
+            }
+            c => {
+                current.push(c);
+                i += 1;
+            }
+        }
+    }
+    flush_word!();
+    Ok(lexemes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_simple_pipeline() {
+        let block = Parser::parse("echo hi | wc -l").expect("parse failed");
+        assert_eq!(
+            block,
+            vec![Statement::Pipeline(vec![
+                vec!["echo".to_string(), "hi".to_string()],
+                vec!["wc".to_string(), "-l".to_string()],
+            ])]
+        );
+    }
+
+    #[test]
+    fn test_parse_if_else() {
+        let block =
+            Parser::parse("if true; then echo yes; else echo no; fi").expect("parse failed");
+        assert_eq!(
+            block,
+            vec![Statement::If(
+                vec![Statement::Pipeline(vec![vec!["true".to_string()]])],
+                vec![Statement::Pipeline(vec![vec![
+                    "echo".to_string(),
+                    "yes".to_string()
+                ]])],
+                Some(vec![Statement::Pipeline(vec![vec![
+                    "echo".to_string(),
+                    "no".to_string()
+                ]])]),
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_if_elif_chain_shares_one_fi() {
+        let block =
+            Parser::parse("if a; then b; elif c; then d; else e; fi").expect("parse failed");
+        let Statement::If(_, _, Some(else_block)) = &block[0] else {
+            panic!("expected outer if with an else branch");
+        };
+        assert!(matches!(else_block[0], Statement::If(..)));
+    }
+
+    #[test]
+    fn test_parse_while_and_until() {
+        let block = Parser::parse("while true; do echo x; done").expect("parse failed");
+        assert!(matches!(block[0], Statement::While(..)));
+
+        let block = Parser::parse("until false; do echo x; done").expect("parse failed");
+        assert!(matches!(block[0], Statement::Until(..)));
+    }
+
+    #[test]
+    fn test_parse_for_loop() {
+        let block = Parser::parse("for i in a b c; do echo $i; done").expect("parse failed");
+        assert_eq!(
+            block,
+            vec![Statement::For(
+                "i".to_string(),
+                vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                vec![Statement::Pipeline(vec![vec![
+                    "echo".to_string(),
+                    "$i".to_string()
+                ]])],
+            )]
+        );
+    }
+
+    #[test]
+    fn test_parse_case_glued_and_spaced_patterns() {
+        let block = Parser::parse("case $x in a|b) echo ab ;; *) echo other ;; esac")
+            .expect("parse failed");
+        let Statement::Case(word, arms) = &block[0] else {
+            panic!("expected a case statement");
+        };
+        assert_eq!(word, "$x");
+        assert_eq!(arms[0].0, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(arms[1].0, vec!["*".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_missing_fi_errors() {
+        let err = Parser::parse("if true; then echo hi").unwrap_err();
+        assert!(matches!(err, CliError::Tokenization(_)));
+    }
+}
@@ -0,0 +1,141 @@
+/// Pathname (glob) expansion, run after `Expander::expand_tokens` produces
+/// the final per-token strings. Each token carries whether it is still
+/// eligible for globbing (unquoted/`Full` tokens only); a `Raw` or
+/// double-quoted (`Weak`) token's `*`, `?`, or `[...]` must stay literal.
+pub struct PathnameExpander;
+
+impl PathnameExpander {
+    /// Replaces each glob-eligible token that contains `*`, `?`, or `[...]`
+    /// with the sorted list of filesystem paths it matches. A pattern that
+    /// matches nothing, or is ineligible, or has no glob metacharacters,
+    /// passes through unchanged (POSIX no-match behavior).
+    pub fn expand(pieces: Vec<(String, bool)>) -> Vec<String> {
+        let mut out = Vec::with_capacity(pieces.len());
+        for (value, glob_eligible) in pieces {
+            if glob_eligible && has_glob_metachars(&value) {
+                // `require_literal_leading_dot` keeps shell semantics: `*`
+                // alone must not pick up `.hidden` files, but a pattern
+                // whose own basename starts with a literal `.` (`.*`)
+                // should still match them.
+                let options = glob::MatchOptions {
+                    require_literal_leading_dot: !pattern_basename_starts_with_dot(&value),
+                    ..Default::default()
+                };
+                let matches = glob::glob_with(&value, options)
+                    .ok()
+                    .map(|paths| {
+                        let mut matches: Vec<String> = paths
+                            .filter_map(|entry| entry.ok())
+                            .map(|path| path.to_string_lossy().into_owned())
+                            .collect();
+                        matches.sort();
+                        matches
+                    })
+                    .unwrap_or_default();
+
+                if matches.is_empty() {
+                    out.push(value);
+                } else {
+                    out.extend(matches);
+                }
+            } else {
+                out.push(value);
+            }
+        }
+        out
+    }
+}
+
+fn has_glob_metachars(s: &str) -> bool {
+    s.contains('*') || s.contains('?') || s.contains('[')
+}
+
+/// True if the pattern's own basename (the part after its last `/`, if
+/// any) literally starts with `.`, e.g. `dir/.*` or `.secret*`.
+fn pattern_basename_starts_with_dot(pattern: &str) -> bool {
+    let basename = pattern.rsplit('/').next().unwrap_or(pattern);
+    basename.starts_with('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Creates a throwaway directory under the system temp dir containing
+    /// `a.rs`, `b.rs`, and `c.txt`, and returns its path.
+    fn setup_fixture_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "pathname_expander_test_{name}_{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create fixture dir");
+        fs::write(dir.join("a.rs"), "").expect("write a.rs");
+        fs::write(dir.join("b.rs"), "").expect("write b.rs");
+        fs::write(dir.join("c.txt"), "").expect("write c.txt");
+        dir
+    }
+
+    #[test]
+    fn test_glob_expands_matches_sorted() {
+        let dir = setup_fixture_dir("matches_sorted");
+        let pattern = dir.join("*.rs").to_string_lossy().into_owned();
+
+        let out = PathnameExpander::expand(vec![("cat".to_string(), true), (pattern, true)]);
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[0], "cat");
+        assert!(out[1].ends_with("a.rs"));
+        assert!(out[2].ends_with("b.rs"));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_glob_no_match_passes_through_literal() {
+        let dir = setup_fixture_dir("no_match");
+        let pattern = dir.join("*.absent").to_string_lossy().into_owned();
+
+        let out = PathnameExpander::expand(vec![(pattern.clone(), true)]);
+        assert_eq!(out, vec![pattern]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_glob_star_does_not_match_hidden_files() {
+        let dir = setup_fixture_dir("hidden_files");
+        fs::write(dir.join(".secret"), "").expect("write .secret");
+        let pattern = dir.join("*").to_string_lossy().into_owned();
+
+        let out = PathnameExpander::expand(vec![(pattern, true)]);
+        assert!(!out.iter().any(|p| p.ends_with(".secret")));
+        assert!(out.iter().any(|p| p.ends_with("a.rs")));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_glob_leading_dot_pattern_matches_hidden_files() {
+        let dir = setup_fixture_dir("hidden_files_explicit");
+        fs::write(dir.join(".secret"), "").expect("write .secret");
+        let pattern = dir.join(".*").to_string_lossy().into_owned();
+
+        let out = PathnameExpander::expand(vec![(pattern, true)]);
+        assert!(out.iter().any(|p| p.ends_with(".secret")));
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_glob_ineligible_token_untouched() {
+        let dir = setup_fixture_dir("ineligible");
+        let pattern = dir.join("*.rs").to_string_lossy().into_owned();
+
+        let out = PathnameExpander::expand(vec![(pattern.clone(), false)]);
+        assert_eq!(out, vec![pattern]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,136 @@
+use crate::modules::input::errors::{CliError, Result};
+
+/// Splits a raw input line into whitespace-separated pieces, the first stage
+/// of `InputProcessor::process`. Quoting is tracked but
+/// not interpreted here — `'...'`/`"..."` are only recognized so whitespace
+/// and an unescaped closing quote inside them don't end the piece early; the
+/// surrounding quote characters are left in place on the returned string for
+/// `QuoteHandler` to strip and unescape afterward. A `\`-escaped character
+/// is kept as the two-character `\x` pair for the same reason.
+///
+/// A `$(...)` / `$((...))` command or arithmetic substitution and a
+/// `` `...` `` backtick substitution are protected the same way: once one
+/// opens, whitespace inside it doesn't end the piece either, so
+/// `Expander::expand_command_substitutions` always sees the whole span as
+/// one token instead of having it cut apart at the first internal space.
+pub struct Tokenizer;
+
+impl Tokenizer {
+    pub fn tokenize(line: &str) -> Result<Vec<String>> {
+        let mut out = Vec::<String>::new();
+        let mut buf = String::new();
+        let mut chars = line.chars().peekable();
+        let mut quote: Option<char> = None;
+        let mut paren_depth: u32 = 0;
+        let mut in_backtick = false;
+
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (None, ' ' | '\t') if paren_depth == 0 && !in_backtick => {
+                    if !buf.is_empty() {
+                        out.push(std::mem::take(&mut buf));
+                    }
+                }
+                (None, '\'' | '"') => {
+                    quote = Some(c);
+                    buf.push(c);
+                }
+                (Some(q), ch) if ch == q => {
+                    buf.push(ch);
+                    quote = None;
+                }
+                (_, '\\') => {
+                    if let Some(n) = chars.next() {
+                        buf.push('\\');
+                        buf.push(n);
+                    } else {
+                        buf.push('\\');
+                    }
+                }
+                (None, '`') => {
+                    in_backtick = !in_backtick;
+                    buf.push('`');
+                }
+                (None, '(') if buf.ends_with('$') || paren_depth > 0 => {
+                    paren_depth += 1;
+                    buf.push('(');
+                }
+                (None, ')') if paren_depth > 0 => {
+                    paren_depth -= 1;
+                    buf.push(')');
+                }
+                _ => buf.push(c),
+            }
+        }
+
+        if quote.is_some() {
+            return Err(CliError::Tokenization("unclosed quote".to_string()));
+        }
+        if !buf.is_empty() {
+            out.push(buf);
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_splits_on_whitespace() {
+        let tokens = Tokenizer::tokenize("echo  hi   world").unwrap();
+        assert_eq!(tokens, vec!["echo", "hi", "world"]);
+    }
+
+    #[test]
+    fn test_tokenize_keeps_quotes_intact_for_quote_handler() {
+        let tokens = Tokenizer::tokenize(r#"echo "hi there" 'raw $X'"#).unwrap();
+        assert_eq!(tokens, vec!["echo", "\"hi there\"", "'raw $X'"]);
+    }
+
+    #[test]
+    fn test_tokenize_does_not_split_on_whitespace_inside_quotes() {
+        let tokens = Tokenizer::tokenize(r#"echo "a b c""#).unwrap();
+        assert_eq!(tokens, vec!["echo", "\"a b c\""]);
+    }
+
+    #[test]
+    fn test_tokenize_keeps_escaped_characters_as_pairs() {
+        let tokens = Tokenizer::tokenize(r"echo \$HOME").unwrap();
+        assert_eq!(tokens, vec!["echo", r"\$HOME"]);
+    }
+
+    #[test]
+    fn test_tokenize_rejects_unclosed_quote() {
+        let err = Tokenizer::tokenize(r#"echo "unterminated"#).unwrap_err();
+        assert!(matches!(err, CliError::Tokenization(_)));
+    }
+
+    #[test]
+    fn test_tokenize_keeps_pipe_and_redirect_operators_as_their_own_tokens() {
+        let tokens = Tokenizer::tokenize("cat file.txt | grep x > out.txt").unwrap();
+        assert_eq!(
+            tokens,
+            vec!["cat", "file.txt", "|", "grep", "x", ">", "out.txt"]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_does_not_split_on_whitespace_inside_dollar_paren() {
+        let tokens = Tokenizer::tokenize("echo before $(echo inner) after").unwrap();
+        assert_eq!(tokens, vec!["echo", "before", "$(echo inner)", "after"]);
+    }
+
+    #[test]
+    fn test_tokenize_does_not_split_on_whitespace_inside_backticks() {
+        let tokens = Tokenizer::tokenize("echo `echo inner`").unwrap();
+        assert_eq!(tokens, vec!["echo", "`echo inner`"]);
+    }
+
+    #[test]
+    fn test_tokenize_keeps_nested_dollar_paren_as_one_token() {
+        let tokens = Tokenizer::tokenize("$(echo $(echo x))").unwrap();
+        assert_eq!(tokens, vec!["$(echo $(echo x))"]);
+    }
+}
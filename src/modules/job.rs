@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::thread::JoinHandle;
+
+use crate::modules::command::Command;
+use crate::modules::runner::Runner;
+
+/// A finished job's exit status, in the same 0-success/non-zero-error
+/// convention `Runner::execute_outcome` already establishes for `$?`.
+#[derive(Debug, Clone, Default)]
+pub struct JobOutcome {
+    pub code: i32,
+}
+
+enum JobState {
+    Running(JoinHandle<JobOutcome>),
+    Done(JobOutcome),
+}
+
+/// One `cmd &` invocation tracked by a `JobTable`.
+pub struct Job {
+    pub id: usize,
+    pub command_line: String,
+    state: JobState,
+}
+
+impl Job {
+    /// `true` once the background thread has finished and `JobTable` has
+    /// reaped its outcome into `state`.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, JobState::Done(_))
+    }
+
+    pub fn outcome(&self) -> Option<&JobOutcome> {
+        match &self.state {
+            JobState::Done(outcome) => Some(outcome),
+            JobState::Running(_) => None,
+        }
+    }
+}
+
+/// Tracks background (`cmd &`) jobs for one `Repl` session: assigns each an
+/// incrementing ID, runs it on its own thread against a throwaway `Runner`
+/// seeded from a snapshot of the session's `bin_path`/environment — not the
+/// session's own shared `Runner`, since a background job must not race a
+/// foreground command mutating the same instance's `cwd`/env concurrently.
+/// Mirrors the oursh shell's `jobs`/`wait` model the request asks for.
+#[derive(Default)]
+pub struct JobTable {
+    next_id: usize,
+    jobs: Vec<Job>,
+}
+
+impl JobTable {
+    pub fn new() -> Self {
+        Self {
+            next_id: 1,
+            jobs: Vec::new(),
+        }
+    }
+
+    /// Spawns `commands` (a single stage or a pipeline) on a background
+    /// thread, recording `command_line` (the original input text) for
+    /// `jobs` to display. Returns the new job's ID.
+    pub fn spawn(
+        &mut self,
+        command_line: String,
+        commands: Vec<Command>,
+        bin_path: PathBuf,
+        env_vars: HashMap<String, String>,
+    ) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let handle = std::thread::spawn(move || {
+            let mut runner = Runner::new(bin_path, env_vars);
+            if commands.len() == 1 {
+                let command = commands.into_iter().next().expect("len checked above");
+                match runner.execute_outcome(command) {
+                    Ok(result) => JobOutcome {
+                        code: result.code.unwrap_or(1),
+                    },
+                    Err(_) => JobOutcome { code: 1 },
+                }
+            } else {
+                match runner.execute_pipeline_outcome(commands) {
+                    Ok(result) => JobOutcome {
+                        code: result.code.unwrap_or(1),
+                    },
+                    Err(_) => JobOutcome { code: 1 },
+                }
+            }
+        });
+
+        self.jobs.push(Job {
+            id,
+            command_line,
+            state: JobState::Running(handle),
+        });
+        id
+    }
+
+    /// Moves every job whose thread has finished into `JobState::Done`,
+    /// returning the IDs that became done *this call* so `Repl::run` can
+    /// print a `[id] Done` notification exactly once per job.
+    pub fn reap_finished(&mut self) -> Vec<usize> {
+        let mut newly_done = Vec::new();
+        for job in &mut self.jobs {
+            let finished = matches!(&job.state, JobState::Running(handle) if handle.is_finished());
+            if !finished {
+                continue;
+            }
+            if let JobState::Running(handle) =
+                std::mem::replace(&mut job.state, JobState::Done(JobOutcome::default()))
+            {
+                let outcome = handle.join().unwrap_or_default();
+                job.state = JobState::Done(outcome);
+                newly_done.push(job.id);
+            }
+        }
+        newly_done
+    }
+
+    /// All jobs, running or finished, oldest first — for the `jobs` builtin.
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    /// Blocks until `id` (or, if `None`, every still-running job) finishes,
+    /// returning each waited-on job's ID and outcome in job order.
+    pub fn wait(&mut self, id: Option<usize>) -> Vec<(usize, JobOutcome)> {
+        let mut results = Vec::new();
+        for job in &mut self.jobs {
+            if let Some(target) = id {
+                if job.id != target {
+                    continue;
+                }
+            }
+            let state = std::mem::replace(&mut job.state, JobState::Done(JobOutcome::default()));
+            let outcome = match state {
+                JobState::Running(handle) => handle.join().unwrap_or_default(),
+                JobState::Done(outcome) => outcome,
+            };
+            job.state = JobState::Done(outcome.clone());
+            results.push((job.id, outcome));
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::command::Command;
+
+    fn bin_path() -> PathBuf {
+        PathBuf::from(".")
+    }
+
+    #[test]
+    fn test_spawn_runs_command_on_background_thread_and_reaps_it() {
+        let mut table = JobTable::new();
+        let command = Command::new("echo", vec!["hi".to_string()]);
+        let id = table.spawn("echo hi &".to_string(), vec![command], bin_path(), HashMap::new());
+
+        assert_eq!(id, 1);
+        assert_eq!(table.jobs().len(), 1);
+        assert!(!table.jobs()[0].is_done());
+
+        // Jobs finish asynchronously; `wait` blocks until this one does.
+        let results = table.wait(Some(id));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, id);
+        assert_eq!(results[0].1.code, 0);
+        assert!(table.jobs()[0].is_done());
+    }
+
+    #[test]
+    fn test_spawn_assigns_incrementing_ids() {
+        let mut table = JobTable::new();
+        let first = table.spawn(
+            "echo one &".to_string(),
+            vec![Command::new("echo", vec!["one".to_string()])],
+            bin_path(),
+            HashMap::new(),
+        );
+        let second = table.spawn(
+            "echo two &".to_string(),
+            vec![Command::new("echo", vec!["two".to_string()])],
+            bin_path(),
+            HashMap::new(),
+        );
+
+        assert_eq!((first, second), (1, 2));
+        table.wait(None);
+    }
+
+    #[test]
+    fn test_wait_reports_failing_job_exit_code() {
+        let mut table = JobTable::new();
+        let id = table.spawn(
+            "no_such_builtin_xyz &".to_string(),
+            vec![Command::new("no_such_builtin_xyz", vec![])],
+            bin_path(),
+            HashMap::new(),
+        );
+
+        let results = table.wait(Some(id));
+        assert_eq!(results[0].1.code, 1);
+    }
+
+    #[test]
+    fn test_wait_with_no_id_waits_for_every_job() {
+        let mut table = JobTable::new();
+        table.spawn(
+            "echo one &".to_string(),
+            vec![Command::new("echo", vec!["one".to_string()])],
+            bin_path(),
+            HashMap::new(),
+        );
+        table.spawn(
+            "echo two &".to_string(),
+            vec![Command::new("echo", vec!["two".to_string()])],
+            bin_path(),
+            HashMap::new(),
+        );
+
+        let results = table.wait(None);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(_, outcome)| outcome.code == 0));
+    }
+}
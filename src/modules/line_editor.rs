@@ -0,0 +1,236 @@
+use std::io::{self, Read, Write};
+
+use crate::modules::completer::Completer;
+
+// Termios syscalls aren't available without a crate dependency (this
+// workspace has none), so — mirroring `runner.rs`'s own direct `dup2`
+// binding — the handful of calls a raw-mode line editor needs are
+// declared straight against libc instead. Field layout matches glibc's
+// `struct termios` on Linux; the only platform this crate targets.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Termios {
+    c_iflag: u32,
+    c_oflag: u32,
+    c_cflag: u32,
+    c_lflag: u32,
+    c_line: u8,
+    c_cc: [u8; 32],
+    c_ispeed: u32,
+    c_ospeed: u32,
+}
+
+extern "C" {
+    fn tcgetattr(fd: i32, termios_p: *mut Termios) -> i32;
+    fn tcsetattr(fd: i32, optional_actions: i32, termios_p: *const Termios) -> i32;
+    fn isatty(fd: i32) -> i32;
+}
+
+const STDIN_FD: i32 = 0;
+const TCSANOW: i32 = 0;
+const ICANON: u32 = 0o0000002;
+const ECHO: u32 = 0o0000010;
+
+/// Puts stdin into raw(-ish) mode — canonical line buffering and local
+/// echo both off, so individual keystrokes (including Tab) reach
+/// `LineEditor::read_line` before Enter is pressed — and restores the
+/// original settings on drop, regardless of how the editor returns.
+struct RawMode {
+    original: Termios,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let mut original: Termios = unsafe { std::mem::zeroed() };
+        if unsafe { tcgetattr(STDIN_FD, &mut original) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut raw = original;
+        raw.c_lflag &= !(ICANON | ECHO);
+        if unsafe { tcsetattr(STDIN_FD, TCSANOW, &raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { original })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe {
+            tcsetattr(STDIN_FD, TCSANOW, &self.original);
+        }
+    }
+}
+
+/// What a Tab keypress should do for a given word and its completion
+/// candidates — kept as a plain, terminal-free decision so it can be unit
+/// tested on its own, with `LineEditor` only responsible for acting on it
+/// (printing a suffix inline vs. a candidate list) and the raw keystroke
+/// loop around it.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TabOutcome {
+    /// No candidates; nothing to do.
+    NoMatch,
+    /// Exactly one candidate — the text to append to finish it.
+    InlineComplete(String),
+    /// More than one candidate, listed for the user to narrow down.
+    ListCandidates(Vec<String>),
+}
+
+/// Decides what a Tab keypress does for `word` (the partial word under the
+/// cursor) given `candidates` (already filtered to those starting with
+/// `word`, as `Completer::complete` returns them).
+pub fn resolve_tab(word: &str, candidates: &[String]) -> TabOutcome {
+    match candidates {
+        [] => TabOutcome::NoMatch,
+        [only] => TabOutcome::InlineComplete(only[word.len()..].to_string()),
+        many => TabOutcome::ListCandidates(many.to_vec()),
+    }
+}
+
+/// Reads one line of input with Tab-completion, backed by a raw-mode
+/// terminal when stdin is a TTY; falls back to a plain `io::stdin().
+/// read_line` when it isn't (piped input, redirected scripts, tests) —
+/// Tab has no special meaning there anyway, so there's nothing to wire up.
+pub struct LineEditor;
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Returns `Ok(None)` on EOF (e.g. Ctrl-D on an empty line), matching
+    /// `io::stdin().read_line`'s `Ok(0)` convention from the loop this
+    /// replaces.
+    pub fn read_line(
+        &self,
+        prompt: &str,
+        completer: &dyn Completer,
+    ) -> io::Result<Option<String>> {
+        if unsafe { isatty(STDIN_FD) } != 1 {
+            return Self::read_line_plain(prompt);
+        }
+
+        let _raw_mode = match RawMode::enable() {
+            Ok(guard) => guard,
+            Err(_) => return Self::read_line_plain(prompt),
+        };
+
+        print!("{prompt}");
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        let mut stdin = io::stdin();
+        let mut byte = [0u8; 1];
+        loop {
+            if stdin.read(&mut byte)? == 0 {
+                return Ok(if line.is_empty() { None } else { Some(line) });
+            }
+            match byte[0] {
+                b'\n' | b'\r' => {
+                    println!();
+                    return Ok(Some(line));
+                }
+                b'\t' => self.handle_tab(&mut line, prompt, completer)?,
+                0x7f | 0x08 => {
+                    if line.pop().is_some() {
+                        print!("\u{8} \u{8}");
+                        io::stdout().flush()?;
+                    }
+                }
+                0x03 => {
+                    // Ctrl-C abandons the current line, same as an
+                    // interactive bash prompt.
+                    println!();
+                    line.clear();
+                    print!("{prompt}");
+                    io::stdout().flush()?;
+                }
+                // Ctrl-D on an empty line is EOF, as in the plain fallback.
+                0x04 if line.is_empty() => return Ok(None),
+                c if (0x20..0x7f).contains(&c) => {
+                    line.push(c as char);
+                    print!("{}", c as char);
+                    io::stdout().flush()?;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn handle_tab(
+        &self,
+        line: &mut String,
+        prompt: &str,
+        completer: &dyn Completer,
+    ) -> io::Result<()> {
+        let word_start = line
+            .rfind(char::is_whitespace)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let word = &line[word_start..];
+        let candidates = completer.complete(line, line.len());
+
+        match resolve_tab(word, &candidates) {
+            TabOutcome::NoMatch => {}
+            TabOutcome::InlineComplete(suffix) => {
+                print!("{suffix}");
+                io::stdout().flush()?;
+                line.push_str(&suffix);
+            }
+            TabOutcome::ListCandidates(candidates) => {
+                println!();
+                println!("{}", candidates.join("  "));
+                print!("{prompt}{line}");
+                io::stdout().flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_line_plain(prompt: &str) -> io::Result<Option<String>> {
+        print!("{prompt}");
+        io::stdout().flush()?;
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(
+            input.trim_end_matches(['\n', '\r']).to_string(),
+        ))
+    }
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_tab_no_candidates_is_no_match() {
+        assert_eq!(resolve_tab("ec", &[]), TabOutcome::NoMatch);
+    }
+
+    #[test]
+    fn test_resolve_tab_single_candidate_completes_inline() {
+        let candidates = vec!["echo".to_string()];
+        assert_eq!(
+            resolve_tab("ec", &candidates),
+            TabOutcome::InlineComplete("ho".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_tab_multiple_candidates_lists_them() {
+        let candidates = vec!["echo".to_string(), "exit".to_string()];
+        assert_eq!(
+            resolve_tab("e", &candidates),
+            TabOutcome::ListCandidates(candidates)
+        );
+    }
+}
@@ -1,11 +1,21 @@
+pub mod builtins;
 pub mod command;
+pub mod completer;
 pub mod environment;
+pub mod history;
 /// CLI modules for command parsing and REPL implementation
 pub mod init;
 pub mod input;
+pub mod job;
+pub mod line_editor;
 pub mod repl;
 pub mod runner;
 
+pub use builtins::{Builtin, BuiltinRegistry};
 pub use command::Command;
+pub use completer::{Completer, ShellCompleter};
 pub use environment::Environment;
+pub use history::History;
+pub use job::JobTable;
+pub use line_editor::LineEditor;
 pub use input::{InputProcessor, InputProcessorBuilder};
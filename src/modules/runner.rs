@@ -1,20 +1,88 @@
+use crate::modules::builtins::BuiltinRegistry;
 use crate::modules::command::Command;
 use std::collections::HashMap;
-use std::fs::OpenOptions;
-use std::io::{self, Write};
-use std::path::PathBuf;
-use std::process::{Command as StdCommand, Stdio};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command as StdCommand, ExitStatus, Stdio};
+use std::thread;
+
+// No `libc` dependency is available in this crate, so the syscalls
+// `spawn_stage` needs for fd >= 3 redirection — `dup2`, to land an opened
+// file on an arbitrary descriptor in the child before `exec`, and `fcntl`,
+// to clear that descriptor's close-on-exec flag — are declared directly
+// against the platform's C library instead.
+extern "C" {
+    fn dup2(oldfd: i32, newfd: i32) -> i32;
+    fn fcntl(fd: i32, cmd: i32, arg: i32) -> i32;
+}
+
+const F_SETFD: i32 = 2;
 
-/// Runner executes commands, using custom implementations when available
-/// or falling back to system executables
+/// Runner executes commands, checking the built-in registry first, then
+/// falling back to a custom implementation in `bin_path` (and any
+/// `extra_bin_paths`), then a system executable.
 pub struct Runner {
     bin_path: PathBuf,
+    extra_bin_paths: Vec<PathBuf>,
     env_vars: HashMap<String, String>,
+    cwd: PathBuf,
+    builtins: BuiltinRegistry,
+    /// When false, a spawned child's environment is cleared before
+    /// `env_vars` is applied, instead of inheriting this process's own
+    /// environment underneath it — mirrors how a test launcher builds a
+    /// hermetic environment.
+    inherit_env: bool,
+}
+
+/// The outcome of `Runner::execute_status`: captured stdout/stderr plus the
+/// real exit code and, on Unix, the signal that killed the process (never
+/// both `Some` at once). Lets a caller populate a `$?` variable and tell
+/// "exited 1" apart from "killed by SIGSEGV/SIGTERM."
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionResult {
+    pub stdout: String,
+    pub stderr: String,
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+}
+
+/// Internal result of running every stage of a pipeline to completion —
+/// shared by `execute_pipeline`, `execute_pipeline_pipefail` and
+/// `execute_pipeline_outcome`, each of which turns it into the `io::Result`
+/// shape its own callers expect. `status` is `None` only for an empty
+/// pipeline, which has no stage to report a status for.
+struct PipelineOutcome {
+    stdout: String,
+    status: Option<ExitStatus>,
+    first_failed_stage: Option<usize>,
+    stage_names: Vec<String>,
 }
 
 impl Runner {
     pub fn new(bin_path: PathBuf, env_vars: HashMap<String, String>) -> Self {
-        Self { bin_path, env_vars }
+        Self::with_builtins(bin_path, env_vars, BuiltinRegistry::with_defaults())
+    }
+
+    /// Same as `new`, but with a caller-supplied built-in registry instead
+    /// of the default coreutils-style set — lets callers register extra
+    /// or replacement built-ins at construction time.
+    pub fn with_builtins(
+        bin_path: PathBuf,
+        env_vars: HashMap<String, String>,
+        builtins: BuiltinRegistry,
+    ) -> Self {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self {
+            bin_path,
+            extra_bin_paths: Vec::new(),
+            env_vars,
+            cwd,
+            builtins,
+            inherit_env: true,
+        }
     }
 
     /// Set an environment variable
@@ -27,142 +95,662 @@ impl Runner {
         self.env_vars.get(key)
     }
 
-    /// Execute a command using custom implementation or system executable
-    pub fn execute(&self, command: Command) -> io::Result<String> {
-        let custom_binary_path = self.bin_path.join(&command.name);
+    /// All currently-set variables, in arbitrary order — mirrors
+    /// `Environment::vars`, for a caller (e.g. command substitution) that
+    /// needs to seed a fresh `Environment` from this runner's own view of
+    /// the session's variables rather than reading them one key at a time.
+    pub fn env_vars(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.env_vars.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
 
-        if custom_binary_path.exists() {
-            self.execute_custom_binary(&command, &custom_binary_path)
-        } else {
-            self.execute_system_command(&command)
-        }
+    /// Adds another directory to search for a custom binary, after
+    /// `bin_path` and any directories added earlier.
+    pub fn add_bin_path(&mut self, path: PathBuf) {
+        self.extra_bin_paths.push(path);
     }
 
-    /// Execute a custom binary from our bin directory
-    fn execute_custom_binary(
-        &self,
-        command: &Command,
-        binary_path: &PathBuf,
-    ) -> io::Result<String> {
-        eprintln!("Executing custom binary: {:?}", binary_path);
-        let mut cmd = StdCommand::new(binary_path);
-        cmd.args(&command.args);
+    /// Controls whether a spawned child inherits this process's own
+    /// environment underneath `env_vars` (the default) or starts from a
+    /// cleared environment, for reproducible, sandbox-style execution.
+    pub fn set_inherit_env(&mut self, inherit: bool) {
+        self.inherit_env = inherit;
+    }
 
-        for (key, value) in &self.env_vars {
-            cmd.env(key, value);
+    /// Execute a command, checking built-ins first (so `cd` and
+    /// `export`-style assignments can mutate the runner's own environment
+    /// and working directory), then a custom binary, then a system
+    /// executable. Thin wrapper over `execute_status` kept for source
+    /// compatibility: a non-zero exit (or a killing signal) collapses to a
+    /// generic `io::Error`, same as before `execute_status` existed.
+    pub fn execute(&mut self, command: Command) -> io::Result<String> {
+        if let Some(builtin) = self.builtins.get(&command.name) {
+            let output = builtin.run(
+                &command.args,
+                command.stdin.as_deref(),
+                &mut self.env_vars,
+                &mut self.cwd,
+            )?;
+            return Self::redirect_builtin_output(&self.cwd, &command, output);
         }
 
-        if command.stdin.is_some() {
-            cmd.stdin(Stdio::piped());
+        let result = self.execute_status(command)?;
+        if result.code == Some(0) {
+            Ok(result.stdout)
+        } else {
+            // The exit code (or terminating signal) must stay visible here
+            // even when stderr was redirected to a file and so isn't
+            // captured in `result.stderr` — otherwise a caller stuck
+            // string-matching this error has no way to tell failures apart.
+            let reason = match (result.code, result.signal) {
+                (Some(code), _) => format!("exit code {code}"),
+                (None, Some(signal)) => format!("signal {signal}"),
+                (None, None) => "unknown reason".to_string(),
+            };
+            if result.stderr.is_empty() {
+                Err(io::Error::other(format!("Command failed ({reason})")))
+            } else {
+                Err(io::Error::other(format!(
+                    "Command failed ({reason}): {}",
+                    result.stderr
+                )))
+            }
         }
+    }
 
-        // Handle stdout redirection
-        if let Some(stdout_file) = &command.stdout {
-            let file = if command.append_stdout {
-                OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(stdout_file)?
-            } else {
-                OpenOptions::new()
-                    .create(true)
-                    .write(true)
-                    .truncate(true)
-                    .open(stdout_file)?
+    /// Execute a command and surface a real exit code for every path,
+    /// including built-ins — which have no OS-level exit status of their
+    /// own, so success collapses to code `0` and an error to code `1`, the
+    /// same convention a real shell uses for a failing built-in. This is
+    /// what lets a caller (`Executor::run_pipeline`) populate `$?`/`$status`
+    /// after *any* command, not just an external one.
+    pub fn execute_outcome(&mut self, command: Command) -> io::Result<ExecutionResult> {
+        if let Some(builtin) = self.builtins.get(&command.name) {
+            return match builtin.run(
+                &command.args,
+                command.stdin.as_deref(),
+                &mut self.env_vars,
+                &mut self.cwd,
+            ) {
+                Ok(output) => {
+                    let stdout = Self::redirect_builtin_output(&self.cwd, &command, output)?;
+                    Ok(ExecutionResult {
+                        stdout,
+                        stderr: String::new(),
+                        code: Some(0),
+                        signal: None,
+                    })
+                }
+                Err(e) => Ok(ExecutionResult {
+                    stdout: String::new(),
+                    stderr: e.to_string(),
+                    code: Some(1),
+                    signal: None,
+                }),
             };
-            cmd.stdout(Stdio::from(file));
-        } else {
-            cmd.stdout(Stdio::piped());
         }
 
-        cmd.stderr(Stdio::piped());
+        self.execute_status(command)
+    }
+
+    /// Execute a command the same way `execute` resolves it (custom binary,
+    /// then system executable — built-ins are handled in `execute` itself
+    /// since they have no OS-level exit status), but surface the real exit
+    /// code and, on Unix, the terminating signal instead of collapsing
+    /// every non-zero outcome into an `io::Error`. This is what lets a
+    /// caller populate a `$?` variable and tell "exited 1" apart from
+    /// "killed by SIGSEGV/SIGTERM."
+    pub fn execute_status(&self, command: Command) -> io::Result<ExecutionResult> {
+        self.execute_status_streaming(command, None)
+    }
+
+    /// Same as `execute_status`, but if `stdout_sink` is given, stdout is
+    /// copied there incrementally instead of being accumulated into
+    /// `ExecutionResult::stdout` — lets a caller stream gigabyte-scale
+    /// output without holding all of it in memory. Stdin is fed from a
+    /// dedicated thread while stdout/stderr drain concurrently, so a child
+    /// that fills its stdout pipe before finishing stdin can't deadlock us.
+    pub fn execute_status_streaming(
+        &self,
+        command: Command,
+        stdout_sink: Option<&mut dyn Write>,
+    ) -> io::Result<ExecutionResult> {
+        let stdin_stdio = match command.stdin {
+            Some(_) => Stdio::piped(),
+            None => Stdio::inherit(),
+        };
+        let (stdout_stdio, stdout_file) = Self::resolve_stdout_stdio(&self.cwd, &command)?;
+        let stderr_stdio = Self::resolve_stderr_stdio(&self.cwd, &command, stdout_file.as_ref())?;
+
+        let child = self.spawn_stage(&command, stdin_stdio, stdout_stdio, stderr_stdio)?;
+        let (stdout_bytes, stderr_bytes, status) =
+            Self::drain_child(child, command.stdin.as_deref(), stdout_sink)?;
+
+        // True merging (same fd for both streams) only happens when stdout
+        // is itself file-redirected — `spawn_stage` opens that same file
+        // for stderr too. When stdout is piped back to us instead, the
+        // child's stdout/stderr are still two separate pipes, so we
+        // approximate `2>&1` by folding the captured stderr onto the end
+        // of stdout here.
+        let merged_into_stdout = command.merge_stderr_into_stdout
+            && command.stdout.is_none()
+            && !stderr_bytes.is_empty();
+        let stdout = if command.stdout.is_some() {
+            String::new()
+        } else if merged_into_stdout {
+            let mut combined = stdout_bytes;
+            combined.extend_from_slice(&stderr_bytes);
+            String::from_utf8_lossy(&combined).to_string()
+        } else {
+            String::from_utf8_lossy(&stdout_bytes).to_string()
+        };
+        let stderr = if merged_into_stdout {
+            String::new()
+        } else {
+            String::from_utf8_lossy(&stderr_bytes).to_string()
+        };
+
+        Ok(ExecutionResult {
+            stdout,
+            stderr,
+            code: status.code(),
+            signal: status.signal(),
+        })
+    }
+
+    /// Spawns a thread that writes `data` into `stdin` and drops it
+    /// (closing the pipe) once done, so a child blocked reading stdin is
+    /// never starved while this process is busy elsewhere. Shared by
+    /// `drain_child` and `execute_pipeline`'s first stage, the two places
+    /// that write caller-supplied stdin into a spawned child.
+    fn write_stdin_async(
+        stdin: Option<ChildStdin>,
+        data: Option<String>,
+    ) -> thread::JoinHandle<io::Result<()>> {
+        thread::spawn(move || {
+            if let (Some(mut stdin), Some(data)) = (stdin, data) {
+                stdin.write_all(data.as_bytes())?;
+            }
+            Ok(())
+        })
+    }
 
-        let mut child = cmd.spawn()?;
+    /// Same as `write_stdin_async`, but for the raw bytes `execute_pipeline`
+    /// already drained out of a tee'd interior stage, rather than the
+    /// caller-supplied heredoc/here-string text `command.stdin` carries.
+    fn write_stdin_bytes_async(
+        stdin: Option<ChildStdin>,
+        data: Option<Vec<u8>>,
+    ) -> thread::JoinHandle<io::Result<()>> {
+        thread::spawn(move || {
+            if let (Some(mut stdin), Some(data)) = (stdin, data) {
+                stdin.write_all(&data)?;
+            }
+            Ok(())
+        })
+    }
 
-        // Write to stdin if provided
-        if let Some(stdin_data) = &command.stdin {
-            if let Some(stdin) = child.stdin.take() {
-                let mut stdin = stdin;
-                stdin.write_all(stdin_data.as_bytes())?;
+    /// Runs `child` to completion without the classic pipe-buffer deadlock:
+    /// `stdin_data` is written on a dedicated thread, stderr is drained on
+    /// a second thread, and stdout is drained on this thread — either into
+    /// a `Vec<u8>` or, if `stdout_sink` is given, streamed straight there —
+    /// so none of the three can block waiting on another.
+    fn drain_child(
+        mut child: Child,
+        stdin_data: Option<&str>,
+        stdout_sink: Option<&mut dyn Write>,
+    ) -> io::Result<(Vec<u8>, Vec<u8>, ExitStatus)> {
+        let stdin_thread =
+            Self::write_stdin_async(child.stdin.take(), stdin_data.map(String::from));
+
+        let mut stderr = child.stderr.take();
+        let stderr_thread = thread::spawn(move || -> io::Result<Vec<u8>> {
+            let mut buf = Vec::new();
+            if let Some(stderr) = stderr.as_mut() {
+                stderr.read_to_end(&mut buf)?;
+            }
+            Ok(buf)
+        });
+
+        let mut stdout_buf = Vec::new();
+        if let Some(mut stdout) = child.stdout.take() {
+            match stdout_sink {
+                Some(sink) => {
+                    io::copy(&mut stdout, sink)?;
+                }
+                None => {
+                    stdout.read_to_end(&mut stdout_buf)?;
+                }
             }
         }
 
-        let output = child.wait_with_output()?;
+        let status = child.wait()?;
+        stdin_thread
+            .join()
+            .map_err(|_| io::Error::other("stdin writer thread panicked"))??;
+        let stderr_buf = stderr_thread
+            .join()
+            .map_err(|_| io::Error::other("stderr reader thread panicked"))??;
 
-        if output.status.success() {
-            // If stdout was redirected to file, return empty string (no output to display)
-            if command.stdout.is_some() {
+        Ok((stdout_buf, stderr_buf, status))
+    }
+
+    /// Applies the same stdout file-redirection rules external commands
+    /// get: if `command.stdout` is set, write there instead of returning
+    /// the text, so a built-in behaves like any other command under `>`
+    /// and `>>`.
+    fn redirect_builtin_output(cwd: &Path, command: &Command, output: String) -> io::Result<String> {
+        match &command.stdout {
+            Some(stdout_file) => {
+                let mut file = Self::open_redirect_file(cwd, stdout_file, command.append_stdout)?;
+                file.write_all(output.as_bytes())?;
                 Ok(String::new())
-            } else {
-                Ok(String::from_utf8_lossy(&output.stdout).to_string())
             }
+            None => Ok(output),
+        }
+    }
+
+    /// Opens `path` for redirected output, truncating it first unless
+    /// `append` is set — the create/append/truncate rules shared by every
+    /// stdout- and stderr-redirection call site. A relative `path` resolves
+    /// against `cwd` (the session's `cd`-tracked directory), not whatever
+    /// directory this process itself happens to be running in.
+    fn open_redirect_file(cwd: &Path, path: &str, append: bool) -> io::Result<std::fs::File> {
+        let path = Self::resolve_redirect_path(cwd, path);
+        if append {
+            OpenOptions::new().create(true).append(true).open(path)
         } else {
-            let error = String::from_utf8_lossy(&output.stderr);
-            Err(io::Error::other(format!("Command failed: {}", error)))
+            OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(path)
         }
     }
 
-    /// Execute a system command (fallback when no custom implementation exists)
-    fn execute_system_command(&self, command: &Command) -> io::Result<String> {
-        let mut cmd = StdCommand::new(&command.name);
-        cmd.args(&command.args);
+    /// Joins a relative redirection path against `cwd`; an absolute path is
+    /// returned unchanged.
+    fn resolve_redirect_path(cwd: &Path, path: &str) -> PathBuf {
+        let candidate = Path::new(path);
+        if candidate.is_absolute() {
+            candidate.to_path_buf()
+        } else {
+            cwd.join(candidate)
+        }
+    }
 
-        for (key, value) in &self.env_vars {
-            cmd.env(key, value);
+    /// Resolves `command`'s stdout `Stdio`, opening its redirect file if
+    /// any. Also hands back that open `File` (when there is one) so
+    /// `resolve_stderr_stdio` can `try_clone` the exact same OS file
+    /// description for a `2>&1`-style merge, instead of racing a second,
+    /// independent open of the same path.
+    fn resolve_stdout_stdio(cwd: &Path, command: &Command) -> io::Result<(Stdio, Option<std::fs::File>)> {
+        match &command.stdout {
+            Some(stdout_file) => {
+                let file = Self::open_redirect_file(cwd, stdout_file, command.append_stdout)?;
+                let stdio = Stdio::from(file.try_clone()?);
+                Ok((stdio, Some(file)))
+            }
+            None => Ok((Stdio::piped(), None)),
+        }
+    }
+
+    /// Executes a `|`-chained pipeline, connecting each stage's stdout
+    /// directly to the next stage's stdin via `Stdio::piped()` instead of
+    /// buffering through a `String` between stages, so data flows while
+    /// every child runs concurrently. The first stage honors
+    /// `command.stdin`; the last stage honors `command.stdout`/
+    /// `append_stdout` file redirection; the returned result reflects the
+    /// final stage's exit status, matching a real shell pipeline. Mixed
+    /// custom-binary and system-executable stages are supported since both
+    /// resolve to a spawned `Child`.
+    ///
+    /// An interior stage (e.g. the `cmd1` in `cmd1 > file | cmd2`) that also
+    /// redirects its stdout is "tee'd": its output is drained into memory,
+    /// written to the file, and then relayed into the next stage's stdin on
+    /// a writer thread, same as `command.stdin` is today. That drain is a
+    /// synchronization point for that one stage only — a pipeline with no
+    /// interior redirects stays fully streaming via `PipeHandoff::Streamed`.
+    pub fn execute_pipeline(&self, commands: Vec<Command>) -> io::Result<String> {
+        let outcome = self.execute_pipeline_stages(commands)?;
+        if outcome.status.map(|s| s.success()).unwrap_or(true) {
+            Ok(outcome.stdout)
+        } else {
+            Err(io::Error::other(
+                "Pipeline failed: final stage exited with a non-zero status",
+            ))
+        }
+    }
+
+    /// Same as `execute_pipeline`, but when `pipefail` is set the pipeline
+    /// fails on the first stage (in pipeline order, not completion order)
+    /// with a non-zero exit instead of only checking the last stage —
+    /// mirroring bash's `set -o pipefail`.
+    pub fn execute_pipeline_pipefail(&self, commands: Vec<Command>) -> io::Result<String> {
+        let outcome = self.execute_pipeline_stages(commands)?;
+        if let Some(stage) = outcome.first_failed_stage {
+            Err(io::Error::other(format!(
+                "Pipeline failed: stage {} ({}) exited with a non-zero status",
+                stage, outcome.stage_names[stage]
+            )))
+        } else {
+            Ok(outcome.stdout)
+        }
+    }
+
+    /// Same as `execute_pipeline`, but surfaces the final stage's real exit
+    /// code (and, on Unix, terminating signal) instead of collapsing a
+    /// non-zero outcome into an `io::Error` — the pipeline counterpart to
+    /// `execute_outcome`, and what lets `Repl` populate `$?`/`$status` after
+    /// a multi-stage pipeline the same way it already does after a single
+    /// command.
+    pub fn execute_pipeline_outcome(&self, commands: Vec<Command>) -> io::Result<ExecutionResult> {
+        let outcome = self.execute_pipeline_stages(commands)?;
+        let (code, signal) = match outcome.status {
+            Some(status) => (status.code(), status.signal()),
+            None => (Some(0), None),
+        };
+        Ok(ExecutionResult {
+            stdout: outcome.stdout,
+            stderr: String::new(),
+            code,
+            signal,
+        })
+    }
+
+    fn execute_pipeline_stages(&self, commands: Vec<Command>) -> io::Result<PipelineOutcome> {
+        if commands.is_empty() {
+            return Ok(PipelineOutcome {
+                stdout: String::new(),
+                status: None,
+                first_failed_stage: None,
+                stage_names: Vec::new(),
+            });
         }
 
-        if command.stdin.is_some() {
-            cmd.stdin(Stdio::piped());
+        // Hands a stage's output to the next stage's stdin: either a real OS
+        // pipe (the common, fully-streamed case) or bytes already drained
+        // into memory, when the stage in between also tee'd to a file.
+        enum PipeHandoff {
+            Streamed(std::process::ChildStdout),
+            Buffered(Vec<u8>),
         }
 
-        // Handle stdout redirection
-        if let Some(stdout_file) = &command.stdout {
-            let file = if command.append_stdout {
-                OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(stdout_file)?
+        let last_index = commands.len() - 1;
+        let mut children = Vec::with_capacity(commands.len());
+        let mut prev_output: Option<PipeHandoff> = None;
+
+        for (i, command) in commands.iter().enumerate() {
+            let (stdin, buffered_stdin) = if i == 0 {
+                match &command.stdin {
+                    Some(_) => (Stdio::piped(), None),
+                    None => (Stdio::inherit(), None),
+                }
             } else {
-                OpenOptions::new()
-                    .create(true)
-                    .write(true)
-                    .truncate(true)
-                    .open(stdout_file)?
+                match prev_output
+                    .take()
+                    .expect("every non-first stage follows a prior stage's output")
+                {
+                    PipeHandoff::Streamed(stdout) => (Stdio::from(stdout), None),
+                    PipeHandoff::Buffered(bytes) => (Stdio::piped(), Some(bytes)),
+                }
             };
-            cmd.stdout(Stdio::from(file));
-        } else {
-            cmd.stdout(Stdio::piped());
+
+            let (stdout, stdout_file) = if i == last_index {
+                Self::resolve_stdout_stdio(&self.cwd, command)?
+            } else {
+                (Stdio::piped(), None)
+            };
+            let stderr = Self::resolve_stderr_stdio(&self.cwd, command, stdout_file.as_ref())?;
+
+            let mut child = self.spawn_stage(command, stdin, stdout, stderr)?;
+
+            let stdin_thread = if i == 0 {
+                Some(Self::write_stdin_async(
+                    child.stdin.take(),
+                    command.stdin.clone(),
+                ))
+            } else if buffered_stdin.is_some() {
+                Some(Self::write_stdin_bytes_async(
+                    child.stdin.take(),
+                    buffered_stdin,
+                ))
+            } else {
+                None
+            };
+
+            if i != last_index {
+                prev_output = Some(match &command.stdout {
+                    Some(stdout_file) => {
+                        let mut buf = Vec::new();
+                        if let Some(mut stdout) = child.stdout.take() {
+                            stdout.read_to_end(&mut buf)?;
+                        }
+                        let mut file = Self::open_redirect_file(&self.cwd, stdout_file, command.append_stdout)?;
+                        file.write_all(&buf)?;
+                        PipeHandoff::Buffered(buf)
+                    }
+                    None => PipeHandoff::Streamed(
+                        child
+                            .stdout
+                            .take()
+                            .expect("interior stage stdout is always piped"),
+                    ),
+                });
+            }
+
+            children.push((child, stdin_thread));
         }
 
-        cmd.stderr(Stdio::piped());
+        let last_command = &commands[last_index];
+        let mut final_output = Vec::new();
+        let mut final_status: Option<ExitStatus> = None;
+        let mut first_failed_stage: Option<usize> = None;
+        for (i, (child, stdin_thread)) in children.into_iter().enumerate() {
+            if i == last_index {
+                let output = child.wait_with_output()?;
+                final_output = output.stdout;
+                // Same approximation as `execute_status_streaming`: true fd
+                // merging only happens when stdout is file-redirected (see
+                // `resolve_stderr_stdio`); otherwise fold the captured
+                // stderr onto the end of stdout here.
+                if last_command.merge_stderr_into_stdout && last_command.stdout.is_none() {
+                    final_output.extend_from_slice(&output.stderr);
+                }
+                if !output.status.success() && first_failed_stage.is_none() {
+                    first_failed_stage = Some(i);
+                }
+                final_status = Some(output.status);
+            } else {
+                let status = child.wait_with_output()?.status;
+                if !status.success() && first_failed_stage.is_none() {
+                    first_failed_stage = Some(i);
+                }
+            }
+            if let Some(stdin_thread) = stdin_thread {
+                stdin_thread
+                    .join()
+                    .map_err(|_| io::Error::other("stdin writer thread panicked"))??;
+            }
+        }
 
-        let mut child = cmd.spawn().map_err(|e| {
+        Ok(PipelineOutcome {
+            stdout: String::from_utf8_lossy(&final_output).to_string(),
+            status: final_status,
+            first_failed_stage,
+            stage_names: commands.iter().map(|c| c.name.clone()).collect(),
+        })
+    }
+
+    /// Resolves `command` to a custom binary (if one exists in `bin_path`)
+    /// or a system executable, then spawns it with the given stdin/stdout/
+    /// stderr `Stdio`, already resolved by the caller (`resolve_stdout_stdio`/
+    /// `resolve_stderr_stdio`) so a `2>&1` merge can share one open file
+    /// handle. Shared by `execute_pipeline` so each stage can be wired to
+    /// its neighbors before any of them run.
+    fn spawn_stage(
+        &self,
+        command: &Command,
+        stdin: Stdio,
+        stdout: Stdio,
+        stderr: Stdio,
+    ) -> io::Result<std::process::Child> {
+        let mut cmd = match self.resolve_custom_binary(&command.name) {
+            Some(custom_binary_path) => StdCommand::new(custom_binary_path),
+            None => StdCommand::new(self.resolve_system_command(&command.name)),
+        };
+        cmd.args(&command.args);
+        // Every spawned child inherits the directory `cd` has tracked for
+        // this session, not just whatever directory the process itself
+        // happened to start in.
+        cmd.current_dir(&self.cwd);
+
+        if !self.inherit_env {
+            cmd.env_clear();
+        }
+        for (key, value) in &self.env_vars {
+            cmd.env(key, value);
+        }
+        // Per-command assignments (`FOO=bar cmd`) are layered on top of and
+        // can override the runner's base environment, but only for this
+        // command's child process — `self.env_vars` itself is untouched.
+        for (key, value) in &command.env {
+            cmd.env(key, value);
+        }
+
+        cmd.stdin(stdin);
+        cmd.stdout(stdout);
+        cmd.stderr(stderr);
+
+        if !command.extra_redirects.is_empty() {
+            let extra_files = Self::open_extra_redirect_files(&self.cwd, command)?;
+            // Safety: the closure only calls `dup2`/`fcntl` on fds we just
+            // opened ourselves and touches no other process state, so it's
+            // safe to run between `fork` and `exec` in the child.
+            unsafe {
+                cmd.pre_exec(move || {
+                    for (fd, file) in &extra_files {
+                        let raw = file.as_raw_fd();
+                        // When `raw` already equals the target fd, `dup2` is
+                        // a documented no-op that leaves the close-on-exec
+                        // flag `File` sets on every fd it opens untouched —
+                        // clearing it explicitly is what makes the
+                        // redirected fd actually survive into the child.
+                        if raw == *fd as i32 {
+                            if fcntl(raw, F_SETFD, 0) < 0 {
+                                return Err(io::Error::last_os_error());
+                            }
+                            continue;
+                        }
+                        if dup2(raw, *fd as i32) < 0 {
+                            return Err(io::Error::last_os_error());
+                        }
+                    }
+                    Ok(())
+                });
+            }
+        }
+
+        cmd.spawn().map_err(|e| {
             io::Error::new(
                 io::ErrorKind::NotFound,
                 format!("Command '{}' not found: {}", command.name, e),
             )
-        })?;
+        })
+    }
+
+    /// Opens every `command.extra_redirects` file ahead of `spawn`, paired
+    /// with its target fd, so the `pre_exec` closure only has to `dup2`
+    /// already-open files into place rather than touching the filesystem
+    /// between `fork` and `exec` (where allocation and most syscalls other
+    /// than `dup2`/`close` aren't safe to rely on).
+    fn open_extra_redirect_files(cwd: &Path, command: &Command) -> io::Result<Vec<(u32, File)>> {
+        command
+            .extra_redirects
+            .iter()
+            .map(|redirect| {
+                let file = Self::open_extra_redirect_file(
+                    cwd,
+                    &redirect.path,
+                    redirect.append,
+                    redirect.read,
+                )?;
+                Ok((redirect.fd, file))
+            })
+            .collect()
+    }
 
-        // Write to stdin if provided
-        if let Some(stdin_data) = &command.stdin {
-            if let Some(stdin) = child.stdin.take() {
-                let mut stdin = stdin;
-                stdin.write_all(stdin_data.as_bytes())?;
+    /// Like `open_redirect_file`, but also supports opening for reading
+    /// (`N<`) — the one case the stdout/stderr helper never needs.
+    fn open_extra_redirect_file(cwd: &Path, path: &str, append: bool, read: bool) -> io::Result<File> {
+        let path = Self::resolve_redirect_path(cwd, path);
+        if read {
+            OpenOptions::new().read(true).open(path)
+        } else if append {
+            OpenOptions::new().create(true).append(true).open(path)
+        } else {
+            OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(path)
+        }
+    }
+
+    /// Searches `bin_path` and then `extra_bin_paths`, in order, for a
+    /// custom implementation of `name`. Returns the first one that exists.
+    fn resolve_custom_binary(&self, name: &str) -> Option<PathBuf> {
+        std::iter::once(&self.bin_path)
+            .chain(self.extra_bin_paths.iter())
+            .map(|dir| dir.join(name))
+            .find(|candidate| candidate.exists())
+    }
+
+    /// Resolves `name` against the runner's own `PATH` env var rather than
+    /// relying solely on the OS resolver, so a shell script that edits
+    /// `PATH` (e.g. via `export`) has that change honored by subsequent
+    /// lookups. Falls back to the bare name — letting the OS resolve it
+    /// against its own `PATH` — when the runner has no `PATH` set or none
+    /// of its directories contain `name`.
+    fn resolve_system_command(&self, name: &str) -> PathBuf {
+        if let Some(path_var) = self.env_vars.get("PATH") {
+            for dir in std::env::split_paths(path_var) {
+                let candidate = dir.join(name);
+                if candidate.is_file() {
+                    return candidate;
+                }
             }
         }
+        PathBuf::from(name)
+    }
 
-        let output = child.wait_with_output()?;
+    /// Resolves where a stage's stderr should go: piped back to us by
+    /// default, a redirected file honoring `append_stderr`, or — when
+    /// `merge_stderr_into_stdout` is set and `stdout_file` is the file
+    /// stdout was itself just opened against — a `try_clone` of that exact
+    /// `File`, so both fds share one OS-level file description (same
+    /// write offset) the way a real `dup2` would, rather than racing two
+    /// independent opens of the same path. When stdout isn't file-
+    /// redirected, there's nothing to dup before the child spawns, so the
+    /// merge is approximated afterwards by folding captured stderr onto
+    /// stdout (see `execute_status_streaming`).
+    fn resolve_stderr_stdio(
+        cwd: &Path,
+        command: &Command,
+        stdout_file: Option<&std::fs::File>,
+    ) -> io::Result<Stdio> {
+        if command.merge_stderr_into_stdout {
+            return match stdout_file {
+                Some(file) => Ok(Stdio::from(file.try_clone()?)),
+                None => Ok(Stdio::piped()),
+            };
+        }
 
-        if output.status.success() {
-            // If stdout was redirected to file, return empty string (no output to display)
-            if command.stdout.is_some() {
-                Ok(String::new())
-            } else {
-                Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        match &command.stderr {
+            Some(stderr_file) => {
+                let file = Self::open_redirect_file(cwd, stderr_file, command.append_stderr)?;
+                Ok(Stdio::from(file))
             }
-        } else {
-            let error = String::from_utf8_lossy(&output.stderr);
-            Err(io::Error::other(format!("Command failed: {}", error)))
+            None => Ok(Stdio::piped()),
         }
     }
 }
@@ -224,7 +812,7 @@ mod tests {
     fn test_runner_system_command_echo() {
         let bin_path = PathBuf::from("/nonexistent/path");
         let env_vars = HashMap::new();
-        let runner = Runner::new(bin_path, env_vars);
+        let mut runner = Runner::new(bin_path, env_vars);
 
         let cmd = Command::new(
             "echo".to_string(),
@@ -246,23 +834,411 @@ mod tests {
 
     #[test]
     fn test_runner_nonexistent_command() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new("definitely_nonexistent_command_12345".to_string(), vec![]);
+        let result = runner.execute(cmd);
+
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        assert!(error.to_string().contains("not found"));
+    }
+
+    #[test]
+    fn test_execute_status_reports_exit_code() {
         let bin_path = PathBuf::from("/nonexistent/path");
         let env_vars = HashMap::new();
         let runner = Runner::new(bin_path, env_vars);
 
-        let cmd = Command::new("definitely_nonexistent_command_12345".to_string(), vec![]);
+        let cmd = Command::new(
+            "sh".to_string(),
+            vec!["-c".to_string(), "exit 3".to_string()],
+        );
+        let result = runner.execute_status(cmd);
+
+        match result {
+            Ok(status) => {
+                assert_eq!(status.code, Some(3));
+                assert_eq!(status.signal, None);
+            }
+            Err(e) => {
+                println!(
+                    "sh not available for execute_status test (acceptable): {}",
+                    e
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_execute_status_captures_stderr_on_nonzero_exit() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new(
+            "sh".to_string(),
+            vec!["-c".to_string(), "echo oops 1>&2; exit 1".to_string()],
+        );
+        let result = runner.execute_status(cmd);
+
+        match result {
+            Ok(status) => {
+                assert_eq!(status.code, Some(1));
+                assert!(status.stderr.contains("oops"));
+                assert!(status.stdout.is_empty());
+            }
+            Err(e) => {
+                println!(
+                    "sh not available for execute_status test (acceptable): {}",
+                    e
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_execute_status_success_has_zero_code() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new("echo".to_string(), vec!["hi".to_string()]);
+        let result = runner.execute_status(cmd);
+
+        match result {
+            Ok(status) => {
+                assert_eq!(status.code, Some(0));
+                assert_eq!(status.signal, None);
+                assert!(status.stdout.contains("hi"));
+            }
+            Err(e) => {
+                println!(
+                    "echo not available for execute_status test (acceptable): {}",
+                    e
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_execute_outcome_builtin_success_has_zero_code() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new("pwd".to_string(), vec![]);
+        let result = runner.execute_outcome(cmd).unwrap();
+
+        assert_eq!(result.code, Some(0));
+        assert!(!result.stdout.is_empty());
+    }
+
+    #[test]
+    fn test_execute_outcome_builtin_failure_has_nonzero_code() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new(
+            "cd".to_string(),
+            vec!["/definitely/not/a/real/directory".to_string()],
+        );
+        let result = runner.execute_outcome(cmd).unwrap();
+
+        assert_eq!(result.code, Some(1));
+        assert!(result.stdout.is_empty());
+    }
+
+    #[test]
+    fn test_execute_outcome_external_command_reports_exit_code() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new(
+            "sh".to_string(),
+            vec!["-c".to_string(), "exit 7".to_string()],
+        );
+        let result = runner.execute_outcome(cmd);
+
+        match result {
+            Ok(status) => assert_eq!(status.code, Some(7)),
+            Err(e) => println!(
+                "sh not available for execute_outcome test (acceptable): {}",
+                e
+            ),
+        }
+    }
+
+    #[test]
+    fn test_execute_status_large_stdin_does_not_deadlock() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let runner = Runner::new(bin_path, env_vars);
+
+        // Bigger than a typical OS pipe buffer (~64KB) so a naive
+        // write-then-wait implementation would deadlock: the child fills
+        // its stdout pipe before we've finished writing its stdin.
+        let big_input = "x\n".repeat(100_000);
+        let cmd = Command::new("cat".to_string(), vec![]).with_stdin(big_input.clone());
+        let result = runner.execute_status(cmd);
+
+        match result {
+            Ok(status) => {
+                assert_eq!(status.code, Some(0));
+                assert_eq!(status.stdout, big_input);
+            }
+            Err(e) => {
+                println!("cat not available for large-stdin test (acceptable): {}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_execute_status_streaming_writes_to_sink() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new("echo".to_string(), vec!["streamed".to_string()]);
+        let mut sink = Vec::new();
+        let result = runner.execute_status_streaming(cmd, Some(&mut sink));
+
+        match result {
+            Ok(status) => {
+                assert_eq!(status.code, Some(0));
+                // Streamed output goes to the sink, not ExecutionResult::stdout.
+                assert_eq!(status.stdout, "");
+                assert!(String::from_utf8_lossy(&sink).contains("streamed"));
+            }
+            Err(e) => {
+                println!(
+                    "echo not available for streaming execute_status test (acceptable): {}",
+                    e
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_stderr_redirection_write() {
+        let test_dir = env::temp_dir().join("cli_test_stderr");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let error_file = test_dir.join("errors.txt");
+        let error_path = error_file.to_string_lossy().to_string();
+
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new(
+            "sh".to_string(),
+            vec!["-c".to_string(), "echo oops 1>&2".to_string()],
+        )
+        .with_stderr(error_path.clone());
+
+        let result = runner.execute(cmd);
+
+        match result {
+            Ok(output) => {
+                assert_eq!(output, "");
+                if let Ok(file_contents) = fs::read_to_string(&error_file) {
+                    assert!(file_contents.contains("oops"));
+                }
+            }
+            Err(e) => {
+                println!("sh not available for stderr redirection test: {}", e);
+            }
+        }
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_execute_error_reports_exit_code_even_with_stderr_redirected() {
+        let test_dir = env::temp_dir().join("cli_test_stderr_exit_code");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let error_file = test_dir.join("errors.txt");
+        let error_path = error_file.to_string_lossy().to_string();
+
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new(
+            "sh".to_string(),
+            vec!["-c".to_string(), "echo oops 1>&2; exit 7".to_string()],
+        )
+        .with_stderr(error_path.clone());
+
+        let result = runner.execute(cmd);
+
+        match result {
+            Ok(_) => panic!("command exited 7, execute() should have errored"),
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.starts_with("Command failed") {
+                    assert!(
+                        msg.contains("exit code 7"),
+                        "expected exit code in error even though stderr went to a file, got: {msg}"
+                    );
+                } else {
+                    println!("sh not available for stderr exit-code test: {}", msg);
+                }
+            }
+        }
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_stderr_merged_into_stdout_file() {
+        let test_dir = env::temp_dir().join("cli_test_stderr_merge_file");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let output_file = test_dir.join("combined.txt");
+        let output_path = output_file.to_string_lossy().to_string();
+
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new(
+            "sh".to_string(),
+            vec![
+                "-c".to_string(),
+                "echo to-stdout; echo to-stderr 1>&2".to_string(),
+            ],
+        )
+        .with_stdout(output_path.clone())
+        .with_stderr_to_stdout();
+
+        let result = runner.execute(cmd);
+
+        match result {
+            Ok(_) => {
+                if let Ok(file_contents) = fs::read_to_string(&output_file) {
+                    assert!(file_contents.contains("to-stdout"));
+                    assert!(file_contents.contains("to-stderr"));
+                }
+            }
+            Err(e) => {
+                println!("sh not available for stderr merge test: {}", e);
+            }
+        }
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_stderr_merged_into_stdout_captured() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new(
+            "sh".to_string(),
+            vec![
+                "-c".to_string(),
+                "echo to-stdout; echo to-stderr 1>&2".to_string(),
+            ],
+        )
+        .with_stderr_to_stdout();
+
+        let result = runner.execute_status(cmd);
+
+        match result {
+            Ok(status) => {
+                assert!(status.stdout.contains("to-stdout"));
+                assert!(status.stdout.contains("to-stderr"));
+                assert!(status.stderr.is_empty());
+            }
+            Err(e) => {
+                println!("sh not available for captured stderr merge test: {}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_extra_redirect_writes_to_high_fd() {
+        let test_dir = env::temp_dir().join("cli_test_extra_redirect_write");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let output_file = test_dir.join("fd3.txt");
+        let output_path = output_file.to_string_lossy().to_string();
+
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new(
+            "sh".to_string(),
+            vec!["-c".to_string(), "echo on-fd-3 1>&3".to_string()],
+        )
+        .with_extra_redirect(3, output_path.clone(), false, false);
+
+        let result = runner.execute(cmd);
+
+        match result {
+            Ok(_) => {
+                let file_contents = fs::read_to_string(&output_file)
+                    .expect("fd 3's target file should have been written");
+                assert!(file_contents.contains("on-fd-3"));
+            }
+            Err(e) => {
+                println!("sh not available for extra-redirect write test: {}", e);
+            }
+        }
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_extra_redirect_reads_from_high_fd() {
+        let test_dir = env::temp_dir().join("cli_test_extra_redirect_read");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let input_file = test_dir.join("fd3_in.txt");
+        fs::write(&input_file, "hello-from-fd-3\n").expect("Failed to write input file");
+        let input_path = input_file.to_string_lossy().to_string();
+
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(bin_path, env_vars);
+
+        let cmd = Command::new(
+            "sh".to_string(),
+            vec!["-c".to_string(), "read line <&3; echo \"$line\"".to_string()],
+        )
+        .with_extra_redirect(3, input_path.clone(), false, true);
+
         let result = runner.execute(cmd);
 
-        assert!(result.is_err());
-        let error = result.unwrap_err();
-        assert!(error.to_string().contains("not found"));
+        match result {
+            Ok(output) => assert!(output.contains("hello-from-fd-3")),
+            Err(e) => {
+                println!("sh not available for extra-redirect read test: {}", e);
+            }
+        }
+
+        let _ = fs::remove_dir_all(&test_dir);
     }
 
     #[test]
     fn test_runner_empty_command_name() {
         let bin_path = PathBuf::from("/test/bin");
         let env_vars = HashMap::new();
-        let runner = Runner::new(bin_path, env_vars);
+        let mut runner = Runner::new(bin_path, env_vars);
 
         let cmd = Command::new("".to_string(), vec![]);
         let result = runner.execute(cmd);
@@ -275,7 +1251,7 @@ mod tests {
         let bin_path = PathBuf::from("/nonexistent/path");
         let mut env_vars = HashMap::new();
         env_vars.insert("TEST_ENV_VAR".to_string(), "42".to_string());
-        let runner = Runner::new(bin_path, env_vars);
+        let mut runner = Runner::new(bin_path, env_vars);
 
         // Try to run a command that uses environment variables
         // Note: This test might be platform-dependent
@@ -293,6 +1269,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_runner_with_env_overlays_per_command_assignment() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let mut env_vars = HashMap::new();
+        env_vars.insert("TEST_ENV_VAR".to_string(), "base".to_string());
+        let mut runner = Runner::new(bin_path, env_vars.clone());
+
+        let cmd = Command::new("printenv".to_string(), vec!["TEST_ENV_VAR".to_string()])
+            .with_env("TEST_ENV_VAR", "overridden");
+        let result = runner.execute(cmd);
+
+        match result {
+            Ok(output) => {
+                assert!(output.contains("overridden"));
+            }
+            Err(_) => {
+                // printenv might not be available, that's okay for unit tests
+            }
+        }
+
+        // The per-command override must not leak into the runner's own
+        // base environment.
+        assert_eq!(runner.env_vars, env_vars);
+    }
+
     #[test]
     fn test_runner_custom_binary_detection() {
         // Create a temporary directory structure for testing
@@ -316,7 +1317,7 @@ mod tests {
         }
 
         let env_vars = HashMap::new();
-        let runner = Runner::new(bin_dir.clone(), env_vars);
+        let mut runner = Runner::new(bin_dir.clone(), env_vars);
 
         let cmd = Command::new("test_cmd".to_string(), vec![]);
         let result = runner.execute(cmd);
@@ -337,12 +1338,121 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_runner_searches_extra_bin_paths() {
+        let test_dir = env::temp_dir().join("cli_runner_test_extra_bin");
+        let bin_dir = test_dir.join("bin");
+
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&bin_dir).expect("Failed to create test directory");
+
+        let fake_binary = bin_dir.join("extra_cmd");
+        fs::write(&fake_binary, "#!/bin/bash\necho 'extra bin path executed'")
+            .expect("Failed to create test binary");
+
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = fs::metadata(&fake_binary).unwrap().permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(&fake_binary, perms).unwrap();
+        }
+
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(PathBuf::from("/nonexistent/path"), env_vars);
+        runner.add_bin_path(bin_dir.clone());
+
+        let cmd = Command::new("extra_cmd".to_string(), vec![]);
+        let result = runner.execute(cmd);
+
+        let _ = fs::remove_dir_all(&test_dir);
+
+        match result {
+            Ok(output) => {
+                assert!(output.contains("extra bin path executed"));
+            }
+            Err(e) => {
+                println!("Extra bin path test failed (acceptable): {}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_runner_env_clear_drops_parent_environment() {
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(PathBuf::from("/nonexistent/path"), env_vars);
+        runner.set_inherit_env(false);
+
+        // SAFETY: test-only, single-threaded set of a process-wide var that
+        // only this test reads, scoped to this test's lifetime.
+        unsafe {
+            std::env::set_var("CLI_RUNNER_TEST_PARENT_VAR", "should-not-be-inherited");
+        }
+
+        let cmd = Command::new(
+            "sh".to_string(),
+            vec![
+                "-c".to_string(),
+                "echo ${CLI_RUNNER_TEST_PARENT_VAR:-unset}".to_string(),
+            ],
+        );
+        let result = runner.execute(cmd);
+
+        unsafe {
+            std::env::remove_var("CLI_RUNNER_TEST_PARENT_VAR");
+        }
+
+        match result {
+            Ok(output) => {
+                assert!(output.contains("unset"));
+            }
+            Err(e) => {
+                println!("sh not available for env_clear test (acceptable): {}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_runner_path_env_resolution() {
+        let test_dir = env::temp_dir().join("cli_runner_test_path_env");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let fake_binary = test_dir.join("path_cmd");
+        fs::write(&fake_binary, "#!/bin/bash\necho 'found via runner PATH'")
+            .expect("Failed to create test binary");
+
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = fs::metadata(&fake_binary).unwrap().permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(&fake_binary, perms).unwrap();
+        }
+
+        let mut env_vars = HashMap::new();
+        env_vars.insert("PATH".to_string(), test_dir.to_string_lossy().to_string());
+        let mut runner = Runner::new(PathBuf::from("/nonexistent/path"), env_vars);
+
+        let cmd = Command::new("path_cmd".to_string(), vec![]);
+        let result = runner.execute(cmd);
+
+        let _ = fs::remove_dir_all(&test_dir);
+
+        match result {
+            Ok(output) => {
+                assert!(output.contains("found via runner PATH"));
+            }
+            Err(e) => {
+                println!("PATH resolution test failed (acceptable): {}", e);
+            }
+        }
+    }
+
     #[test]
     fn test_command_with_stdin_execution() {
         // Test system cat command with stdin (if available)
         let bin_path = PathBuf::from("/nonexistent/path");
         let env_vars = HashMap::new();
-        let runner = Runner::new(bin_path, env_vars);
+        let mut runner = Runner::new(bin_path, env_vars);
 
         let cmd =
             Command::new("cat".to_string(), vec![]).with_stdin("hello from stdin\n".to_string());
@@ -364,7 +1474,7 @@ mod tests {
     fn test_command_args_handling() {
         let bin_path = PathBuf::from("/nonexistent/path");
         let env_vars = HashMap::new();
-        let runner = Runner::new(bin_path, env_vars);
+        let mut runner = Runner::new(bin_path, env_vars);
 
         // Test with multiple arguments
         let cmd = Command::new(
@@ -403,7 +1513,7 @@ mod tests {
 
         let bin_path = PathBuf::from("/nonexistent/path");
         let env_vars = HashMap::new();
-        let runner = Runner::new(bin_path, env_vars);
+        let mut runner = Runner::new(bin_path, env_vars);
 
         let cmd = Command::new("echo".to_string(), vec!["Hello World".to_string()])
             .with_stdout(output_path.clone());
@@ -448,7 +1558,7 @@ mod tests {
 
         let bin_path = PathBuf::from("/nonexistent/path");
         let env_vars = HashMap::new();
-        let runner = Runner::new(bin_path, env_vars);
+        let mut runner = Runner::new(bin_path, env_vars);
 
         let cmd = Command::new("echo".to_string(), vec!["Appended line".to_string()])
             .with_stdout(output_path.clone())
@@ -480,7 +1590,7 @@ mod tests {
     fn test_stdin_redirection() {
         let bin_path = PathBuf::from("/nonexistent/path");
         let env_vars = HashMap::new();
-        let runner = Runner::new(bin_path, env_vars);
+        let mut runner = Runner::new(bin_path, env_vars);
 
         let input_data = "line1\nline2\nline3\n";
         let cmd = Command::new("cat".to_string(), vec![]).with_stdin(input_data.to_string());
@@ -515,7 +1625,7 @@ mod tests {
 
         let bin_path = PathBuf::from("/nonexistent/path");
         let env_vars = HashMap::new();
-        let runner = Runner::new(bin_path, env_vars);
+        let mut runner = Runner::new(bin_path, env_vars);
 
         let input_data = "test input data\n";
         let cmd = Command::new("cat".to_string(), vec![])
@@ -560,6 +1670,237 @@ mod tests {
         assert!(cmd.append_stdout);
     }
 
+    #[test]
+    fn test_execute_pipeline_two_stages() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let runner = Runner::new(bin_path, env_vars);
+
+        let commands = vec![
+            Command::new("echo".to_string(), vec!["hello pipeline".to_string()]),
+            Command::new("cat".to_string(), vec![]),
+        ];
+        let result = runner.execute_pipeline(commands);
+
+        match result {
+            Ok(output) => {
+                assert!(output.contains("hello pipeline"));
+            }
+            Err(e) => {
+                println!(
+                    "echo/cat not available for pipeline test (acceptable): {}",
+                    e
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_execute_pipeline_honors_first_stage_stdin() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let runner = Runner::new(bin_path, env_vars);
+
+        let commands = vec![
+            Command::new("cat".to_string(), vec![]).with_stdin("piped stdin data\n".to_string()),
+            Command::new("cat".to_string(), vec![]),
+        ];
+        let result = runner.execute_pipeline(commands);
+
+        match result {
+            Ok(output) => {
+                assert!(output.contains("piped stdin data"));
+            }
+            Err(e) => {
+                println!(
+                    "cat not available for pipeline stdin test (acceptable): {}",
+                    e
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_execute_pipeline_last_stage_stdout_redirection() {
+        let test_dir = env::temp_dir().join("cli_test_pipeline_redirect");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let output_file = test_dir.join("pipeline_output.txt");
+        let output_path = output_file.to_string_lossy().to_string();
+
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let runner = Runner::new(bin_path, env_vars);
+
+        let commands = vec![
+            Command::new("echo".to_string(), vec!["redirected output".to_string()]),
+            Command::new("cat".to_string(), vec![]).with_stdout(output_path.clone()),
+        ];
+        let result = runner.execute_pipeline(commands);
+
+        match result {
+            Ok(output) => {
+                assert_eq!(output, "");
+                if let Ok(file_contents) = fs::read_to_string(&output_file) {
+                    assert!(file_contents.contains("redirected output"));
+                }
+            }
+            Err(e) => {
+                println!(
+                    "echo/cat not available for pipeline redirection test (acceptable): {}",
+                    e
+                );
+            }
+        }
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_execute_pipeline_interior_stage_stdout_redirection() {
+        // A `>` on a non-final stage (`echo ... > file | cat`) should
+        // both tee that stage's output to the file and keep it flowing on
+        // through the rest of the pipeline, rather than being dropped.
+        let test_dir = env::temp_dir().join("cli_test_pipeline_interior_redirect");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let output_file = test_dir.join("interior_output.txt");
+        let output_path = output_file.to_string_lossy().to_string();
+
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let runner = Runner::new(bin_path, env_vars);
+
+        let commands = vec![
+            Command::new("echo".to_string(), vec!["teed output".to_string()])
+                .with_stdout(output_path.clone()),
+            Command::new("cat".to_string(), vec![]),
+        ];
+        let result = runner.execute_pipeline(commands);
+
+        match result {
+            Ok(output) => {
+                assert!(output.contains("teed output"));
+                if let Ok(file_contents) = fs::read_to_string(&output_file) {
+                    assert!(file_contents.contains("teed output"));
+                }
+            }
+            Err(e) => {
+                println!(
+                    "echo/cat not available for interior pipeline redirection test (acceptable): {}",
+                    e
+                );
+            }
+        }
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_execute_pipeline_pipefail_catches_middle_stage_failure() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let runner = Runner::new(bin_path, env_vars);
+
+        // A middle stage that exits non-zero; the default (non-pipefail)
+        // pipeline only looks at the last stage, which still succeeds.
+        let commands = vec![
+            Command::new("sh".to_string(), vec!["-c".to_string(), "exit 1".to_string()]),
+            Command::new("cat".to_string(), vec![]),
+        ];
+        let result = runner.execute_pipeline_pipefail(commands);
+
+        match result {
+            Ok(_) => panic!("pipefail should have reported the failing middle stage"),
+            Err(e) => {
+                // Either the pipefail check fired (expected), or `sh`/`cat`
+                // weren't resolvable at all in this environment — both are
+                // `Err`, so just make sure we didn't silently succeed.
+                println!("pipefail test observed: {}", e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_spawn_stage_uses_cd_tracked_cwd_for_child_processes() {
+        use std::env;
+
+        let test_dir = env::temp_dir().join("cli_test_spawn_cwd");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(bin_path, env_vars);
+
+        let cd = Command::new(
+            "cd".to_string(),
+            vec![test_dir.to_string_lossy().to_string()],
+        );
+        runner.execute_outcome(cd).expect("cd should succeed");
+
+        let sh = Command::new(
+            "sh".to_string(),
+            vec!["-c".to_string(), "pwd".to_string()],
+        );
+        match runner.execute_outcome(sh) {
+            Ok(result) if result.code == Some(0) => {
+                assert_eq!(
+                    result.stdout.trim(),
+                    test_dir.canonicalize().unwrap().to_string_lossy()
+                );
+            }
+            other => {
+                println!("sh not available for cwd-inheritance test (acceptable): {:?}", other);
+            }
+        }
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_cd_then_relative_redirection_lands_in_tracked_cwd() {
+        use std::env;
+
+        let test_dir = env::temp_dir().join("cli_test_cd_relative_redirect");
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Failed to create test directory");
+
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let mut runner = Runner::new(bin_path, env_vars);
+
+        let cd = Command::new(
+            "cd".to_string(),
+            vec![test_dir.to_string_lossy().to_string()],
+        );
+        runner.execute_outcome(cd).expect("cd should succeed");
+
+        // "out.txt" is relative and must resolve against the cd-tracked
+        // cwd, not whatever directory this test binary happens to run in.
+        let echo = Command::new("echo".to_string(), vec!["Hello".to_string()])
+            .with_stdout("out.txt");
+        runner.execute_outcome(echo).expect("echo should succeed");
+
+        let written = fs::read_to_string(test_dir.join("out.txt"))
+            .expect("redirected file should exist under the tracked cwd");
+        assert_eq!(written.trim(), "Hello");
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_execute_pipeline_empty_is_noop() {
+        let bin_path = PathBuf::from("/nonexistent/path");
+        let env_vars = HashMap::new();
+        let runner = Runner::new(bin_path, env_vars);
+
+        let result = runner.execute_pipeline(vec![]);
+        assert_eq!(result.unwrap(), "");
+    }
+
     #[test]
     fn test_stdout_file_creation() {
         use std::env;
@@ -576,7 +1917,7 @@ mod tests {
 
         let bin_path = PathBuf::from("/nonexistent/path");
         let env_vars = HashMap::new();
-        let runner = Runner::new(bin_path, env_vars);
+        let mut runner = Runner::new(bin_path, env_vars);
 
         let cmd = Command::new("echo".to_string(), vec!["Creating new file".to_string()])
             .with_stdout(output_path.clone());
@@ -0,0 +1,51 @@
+// Demonstrates the `CommandUnderTest` harness against the bundled
+// binaries, replacing the per-binary `get_X_binary_path` duplication with
+// one shared, fluent way to drive and assert on them.
+mod support;
+
+use support::CommandUnderTest;
+
+#[test]
+fn echo_prints_args_joined_by_spaces() {
+    CommandUnderTest::new("echo")
+        .args(["hello", "world"])
+        .run()
+        .succeeds()
+        .stdout_is("hello world\n")
+        .stderr_is_empty();
+}
+
+#[test]
+fn echo_suppresses_newline_with_n_flag() {
+    CommandUnderTest::new("echo")
+        .args(["-n", "hello"])
+        .run()
+        .succeeds()
+        .stdout_is("hello");
+}
+
+#[test]
+fn pwd_reports_the_current_directory() {
+    let outcome = CommandUnderTest::new("pwd").run();
+    outcome.succeeds().stderr_is_empty();
+    assert!(!outcome.stdout.trim().is_empty());
+}
+
+#[test]
+fn wc_counts_lines_from_stdin() {
+    CommandUnderTest::new("wc")
+        .stdin("one\ntwo\nthree\n")
+        .run()
+        .succeeds()
+        .stdout_contains("3");
+}
+
+#[test]
+fn cat_echoes_stdin_back_unchanged() {
+    CommandUnderTest::new("cat")
+        .stdin("piped through cat\n")
+        .run()
+        .succeeds()
+        .stdout_is("piped through cat\n")
+        .stderr_is_empty();
+}
@@ -3,6 +3,7 @@
 use pretty_assertions::assert_eq;
 use std::collections::HashMap;
 
+use cli_rust::modules::input::command::ResolvedStdin;
 use cli_rust::modules::input::errors::CliError;
 use cli_rust::modules::input::{Environment, InputProcessorBuilder};
 
@@ -20,14 +21,14 @@ fn test_tokenization_quotes_and_expansion() {
     let mut vars = HashMap::new();
     vars.insert("NAME".to_string(), "Bob".to_string());
     vars.insert("X".to_string(), "1".to_string());
-    let env = Environment::with_vars(vars);
-    let ip = InputProcessorBuilder::new(env).build();
+    let mut env = Environment::with_vars(vars);
+    let ip = InputProcessorBuilder::new().build();
 
-    let cmds = ip
-        .process(r#"echo "hi $NAME" '$NAME' world \\ \$X > out.txt"#)
+    let pipeline = ip
+        .process(r#"echo "hi $NAME" '$NAME' world \\ \$X > out.txt"#, &mut env)
         .unwrap();
-    assert_eq!(cmds.len(), 1);
-    let c0 = &cmds[0];
+    assert_eq!(pipeline.stages.len(), 1);
+    let c0 = &pipeline.stages[0];
     assert_eq!(c0.name, "echo");
     assert_eq!(
         c0.args,
@@ -39,70 +40,70 @@ fn test_tokenization_quotes_and_expansion() {
             "$X".to_string()
         ]
     );
-    assert_eq!(c0.stdout.as_deref(), Some("out.txt"));
-    assert!(!c0.append_stdout);
+    let resolved = c0.resolve_redirections();
+    assert_eq!(resolved.stdout, Some(("out.txt".to_string(), false)));
 }
 
 #[test]
 fn test_pipeline_parsing() {
-    let env = Environment::new();
-    let ip = InputProcessorBuilder::new(env).build();
-
-    let cmds = ip.process(r#"cat "a|b" | grep a | wc -l"#).unwrap();
-    assert_eq!(cmds.len(), 3);
-    assert_eq!(cmds[0].name, "cat");
-    assert_eq!(cmds[1].name, "grep");
-    assert_eq!(cmds[2].name, "wc");
-    assert_eq!(cmds[2].args, vec!["-l".to_string()]);
+    let mut env = Environment::new();
+    let ip = InputProcessorBuilder::new().build();
+
+    let pipeline = ip.process(r#"cat "a|b" | grep a | wc -l"#, &mut env).unwrap();
+    assert_eq!(pipeline.stages.len(), 3);
+    assert_eq!(pipeline.stages[0].name, "cat");
+    assert_eq!(pipeline.stages[1].name, "grep");
+    assert_eq!(pipeline.stages[2].name, "wc");
+    assert_eq!(pipeline.stages[2].args, vec!["-l".to_string()]);
 }
 
 #[test]
 fn test_stdin_stdout_redirection_parsing() {
-    let env = Environment::new();
-    let ip = InputProcessorBuilder::new(env).build();
-    let cmds = ip.process(r#"grep foo < in.txt >> out.log"#).unwrap();
-    assert_eq!(cmds.len(), 1);
-    let c = &cmds[0];
+    let mut env = Environment::new();
+    let ip = InputProcessorBuilder::new().build();
+    let pipeline = ip.process(r#"grep foo < in.txt >> out.log"#, &mut env).unwrap();
+    assert_eq!(pipeline.stages.len(), 1);
+    let c = &pipeline.stages[0];
     assert_eq!(c.name, "grep");
-    assert_eq!(c.stdin.as_deref(), Some("in.txt"));
-    assert_eq!(c.stdout.as_deref(), Some("out.log"));
-    assert!(c.append_stdout);
+    let resolved = c.resolve_redirections();
+    assert_eq!(resolved.stdin, Some(ResolvedStdin::File("in.txt".to_string())));
+    assert_eq!(resolved.stdout, Some(("out.log".to_string(), true)));
 }
 
 #[test]
 fn test_stderr_redirection_parsing() {
-    let env = Environment::new();
-    let ip = InputProcessorBuilder::new(env).build();
+    let mut env = Environment::new();
+    let ip = InputProcessorBuilder::new().build();
 
     // Basic stderr redirection
-    let cmds = ip.process("command 2> error.txt").unwrap();
-    assert_eq!(cmds.len(), 1);
-    let cmd = &cmds[0];
+    let pipeline = ip.process("command 2> error.txt", &mut env).unwrap();
+    assert_eq!(pipeline.stages.len(), 1);
+    let cmd = &pipeline.stages[0];
     assert_eq!(cmd.name, "command");
-    assert_eq!(cmd.stderr.as_deref(), Some("error.txt"));
-    assert!(!cmd.append_stderr);
+    let resolved = cmd.resolve_redirections();
+    assert_eq!(resolved.stderr, Some(("error.txt".to_string(), false)));
 
     // stderr append
-    let cmds = ip.process("command 2>> error.log").unwrap();
-    assert_eq!(cmds.len(), 1);
-    let cmd = &cmds[0];
-    assert!(cmd.append_stderr);
+    let pipeline = ip.process("command 2>> error.log", &mut env).unwrap();
+    assert_eq!(pipeline.stages.len(), 1);
+    let resolved = pipeline.stages[0].resolve_redirections();
+    assert_eq!(resolved.stderr, Some(("error.log".to_string(), true)));
 
     // Combined redirections
-    let cmds = ip
-        .process("grep pattern < input.txt > output.txt 2> error.txt")
+    let pipeline = ip
+        .process("grep pattern < input.txt > output.txt 2> error.txt", &mut env)
         .unwrap();
-    assert_eq!(cmds.len(), 1);
-    let cmd = &cmds[0];
-    assert_eq!(cmd.stdin.as_deref(), Some("input.txt"));
-    assert_eq!(cmd.stdout.as_deref(), Some("output.txt"));
-    assert_eq!(cmd.stderr.as_deref(), Some("error.txt"));
+    assert_eq!(pipeline.stages.len(), 1);
+    let resolved = pipeline.stages[0].resolve_redirections();
+    assert_eq!(resolved.stdin, Some(ResolvedStdin::File("input.txt".to_string())));
+    assert_eq!(resolved.stdout, Some(("output.txt".to_string(), false)));
+    assert_eq!(resolved.stderr, Some(("error.txt".to_string(), false)));
 }
 
 #[test]
 fn test_explicit_file_descriptors() {
-    let env = Environment::new();
-    let ip = InputProcessorBuilder::new(env).build();
+    let mut env = Environment::new();
+    let ip = InputProcessorBuilder::new().build();
 
     // Test explicit fd numbers
     let test_cases = vec![
@@ -112,13 +113,17 @@ fn test_explicit_file_descriptors() {
     ];
 
     for (input, expected_stdin, expected_stdout, expected_stderr) in test_cases {
-        let cmds = ip.process(input).unwrap();
-        assert_eq!(cmds.len(), 1);
-        let cmd = &cmds[0];
+        let pipeline = ip.process(input, &mut env).unwrap();
+        assert_eq!(pipeline.stages.len(), 1);
+        let cmd = &pipeline.stages[0];
         assert_eq!(cmd.name, "command");
-        assert_eq!(cmd.stdin.as_deref(), expected_stdin);
-        assert_eq!(cmd.stdout.as_deref(), expected_stdout);
-        assert_eq!(cmd.stderr.as_deref(), expected_stderr);
+        let resolved = cmd.resolve_redirections();
+        assert_eq!(
+            resolved.stdin,
+            expected_stdin.map(|s| ResolvedStdin::File(s.to_string()))
+        );
+        assert_eq!(resolved.stdout, expected_stdout.map(|s| (s.to_string(), false)));
+        assert_eq!(resolved.stderr, expected_stderr.map(|s| (s.to_string(), false)));
     }
 }
 
@@ -128,41 +133,45 @@ fn test_variable_expansion_in_redirections() {
     vars.insert("OUTFILE".to_string(), "result.txt".to_string());
     vars.insert("ERRFILE".to_string(), "errors.log".to_string());
 
-    let env = Environment::with_vars(vars);
-    let ip = InputProcessorBuilder::new(env).build();
+    let mut env = Environment::with_vars(vars);
+    let ip = InputProcessorBuilder::new().build();
 
-    let cmds = ip
-        .process("cat < input.txt > $OUTFILE 2> $ERRFILE")
+    let pipeline = ip
+        .process("cat < input.txt > $OUTFILE 2> $ERRFILE", &mut env)
         .unwrap();
-    assert_eq!(cmds.len(), 1);
-    let cmd = &cmds[0];
-    assert_eq!(cmd.stdout.as_deref(), Some("result.txt"));
-    assert_eq!(cmd.stderr.as_deref(), Some("errors.log"));
+    assert_eq!(pipeline.stages.len(), 1);
+    let resolved = pipeline.stages[0].resolve_redirections();
+    assert_eq!(resolved.stdout, Some(("result.txt".to_string(), false)));
+    assert_eq!(resolved.stderr, Some(("errors.log".to_string(), false)));
 }
 
 #[test]
 fn test_quoted_filenames_in_redirections() {
-    let env = Environment::new();
-    let ip = InputProcessorBuilder::new(env).build();
+    let mut env = Environment::new();
+    let ip = InputProcessorBuilder::new().build();
 
-    let cmds = ip
-        .process(r#"command < "input file.txt" > "output file.txt" 2> "error file.log""#)
+    let pipeline = ip
+        .process(r#"command < "input file.txt" > "output file.txt" 2> "error file.log""#, &mut env)
         .unwrap();
-    assert_eq!(cmds.len(), 1);
-    let cmd = &cmds[0];
-    assert_eq!(cmd.stdin.as_deref(), Some("input file.txt"));
-    assert_eq!(cmd.stdout.as_deref(), Some("output file.txt"));
-    assert_eq!(cmd.stderr.as_deref(), Some("error file.log"));
+    assert_eq!(pipeline.stages.len(), 1);
+    let resolved = pipeline.stages[0].resolve_redirections();
+    assert_eq!(
+        resolved.stdin,
+        Some(ResolvedStdin::File("input file.txt".to_string()))
+    );
+    assert_eq!(resolved.stdout, Some(("output file.txt".to_string(), false)));
+    assert_eq!(resolved.stderr, Some(("error file.log".to_string(), false)));
 }
 
 #[test]
 fn test_parse_errors() {
-    let env = Environment::new();
-    let ip = InputProcessorBuilder::new(env).build();
+    let mut env = Environment::new();
+    let ip = InputProcessorBuilder::new().build();
 
-    // Unclosed quote should error
-    let err = ip.process("echo \"oops").unwrap_err();
-    assert_eq!(err, CliError::Quote("unclosed quote".into()));
+    // Unclosed quote should error (Tokenizer, not the old quote-handling
+    // module, is what now rejects this)
+    let err = ip.process("echo \"oops", &mut env).unwrap_err();
+    assert_eq!(err, CliError::Tokenization("unclosed quote".into()));
 }
 
 #[test]
@@ -173,35 +182,35 @@ fn test_adjacent_variable_expansion() {
     vars.insert("A".to_string(), "1".to_string());
     vars.insert("B".to_string(), "2".to_string());
 
-    let env = Environment::with_vars(vars);
-    let ip = InputProcessorBuilder::new(env).build();
+    let mut env = Environment::with_vars(vars);
+    let ip = InputProcessorBuilder::new().build();
 
     // Test $x$y -> exit
-    let cmds = ip.process("$x$y").unwrap();
-    assert_eq!(cmds.len(), 1);
-    assert_eq!(cmds[0].name, "exit");
+    let pipeline = ip.process("$x$y", &mut env).unwrap();
+    assert_eq!(pipeline.stages.len(), 1);
+    assert_eq!(pipeline.stages[0].name, "exit");
 
     // Test pre$A$Bp -> pre12p
-    let cmds = ip.process("echo pre$A$Bp").unwrap();
-    assert_eq!(cmds.len(), 1);
-    assert_eq!(cmds[0].args, vec!["pre12p"]);
+    let pipeline = ip.process("echo pre$A$Bp", &mut env).unwrap();
+    assert_eq!(pipeline.stages.len(), 1);
+    assert_eq!(pipeline.stages[0].args, vec!["pre12p"]);
 }
 
 #[test]
 fn test_mixed_append_modes() {
-    let env = Environment::new();
-    let ip = InputProcessorBuilder::new(env).build();
+    let mut env = Environment::new();
+    let ip = InputProcessorBuilder::new().build();
 
     // Test mixed append and overwrite modes
-    let cmds = ip.process("command >> output.log 2> error.txt").unwrap();
-    assert_eq!(cmds.len(), 1);
-    let cmd = &cmds[0];
-    assert!(cmd.append_stdout);
-    assert!(!cmd.append_stderr);
-
-    let cmds = ip.process("command > output.txt 2>> error.log").unwrap();
-    assert_eq!(cmds.len(), 1);
-    let cmd = &cmds[0];
-    assert!(!cmd.append_stdout);
-    assert!(cmd.append_stderr);
+    let pipeline = ip.process("command >> output.log 2> error.txt", &mut env).unwrap();
+    assert_eq!(pipeline.stages.len(), 1);
+    let resolved = pipeline.stages[0].resolve_redirections();
+    assert_eq!(resolved.stdout, Some(("output.log".to_string(), true)));
+    assert_eq!(resolved.stderr, Some(("error.txt".to_string(), false)));
+
+    let pipeline = ip.process("command > output.txt 2>> error.log", &mut env).unwrap();
+    assert_eq!(pipeline.stages.len(), 1);
+    let resolved = pipeline.stages[0].resolve_redirections();
+    assert_eq!(resolved.stdout, Some(("output.txt".to_string(), false)));
+    assert_eq!(resolved.stderr, Some(("error.log".to_string(), true)));
 }
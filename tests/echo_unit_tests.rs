@@ -120,8 +120,26 @@ mod echo_unit_tests {
 
     #[test]
     fn test_echo_with_dashes() {
+        // `-n` is a recognized flag so it's consumed; `--help` isn't a pure
+        // `n`/`e`/`E` flag word, so flag parsing stops there.
         let result = run_echo_binary(vec!["-n", "--help", "-"]);
-        assert_eq!(result.stdout, "-n --help -\n");
+        assert_eq!(result.stdout, "--help -");
+        assert_eq!(result.stderr, "");
+        assert_eq!(result.exit_code, 0);
+    }
+
+    #[test]
+    fn test_echo_suppresses_trailing_newline() {
+        let result = run_echo_binary(vec!["-n", "hello"]);
+        assert_eq!(result.stdout, "hello");
+        assert_eq!(result.stderr, "");
+        assert_eq!(result.exit_code, 0);
+    }
+
+    #[test]
+    fn test_echo_interprets_escapes() {
+        let result = run_echo_binary(vec!["-e", "a\\tb"]);
+        assert_eq!(result.stdout, "a\tb\n");
         assert_eq!(result.stderr, "");
         assert_eq!(result.exit_code, 0);
     }
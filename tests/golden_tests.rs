@@ -0,0 +1,97 @@
+// Regression cases for builtins, redirection and pipelines expressed as
+// plain-text fixtures instead of Rust assertions — see
+// `tests/support/golden.rs` for the directive grammar `run_fixture` parses.
+mod support;
+
+use support::run_fixture;
+
+#[test]
+fn echo_builtin_writes_its_args_and_a_trailing_newline() {
+    run_fixture(
+        "\
+#command echo hello world
+#stdout
+hello world
+#status 0
+",
+    );
+}
+
+#[test]
+fn cat_reads_an_infile_through_stdin_redirection() {
+    run_fixture(
+        "\
+#command cat < greeting.txt
+#infile greeting.txt
+hello from a fixture
+#stdout
+hello from a fixture
+#status 0
+",
+    );
+}
+
+#[test]
+fn cat_writes_stdout_redirection_to_an_outfile() {
+    run_fixture(
+        "\
+#command cat < in.txt > out.txt
+#infile in.txt
+one
+two
+#outfile out.txt
+one
+two
+#status 0
+",
+    );
+}
+
+#[test]
+fn nonewline_matches_output_with_no_trailing_newline() {
+    run_fixture(
+        "\
+#command printf hi
+#stdout
+hi
+#nonewline
+#status 0
+",
+    );
+}
+
+#[test]
+fn pipeline_stage_output_feeds_the_next_stage() {
+    run_fixture(
+        "\
+#command echo one two three | wc -w
+#stdout
+3
+#status 0
+",
+    );
+}
+
+#[test]
+fn dollar_paren_command_substitution_splices_captured_stdout() {
+    run_fixture(
+        "\
+#command echo before $(echo middle) after
+#stdout
+before middle after
+#status 0
+",
+    );
+}
+
+#[test]
+fn backtick_command_substitution_is_equivalent_to_dollar_paren() {
+    run_fixture(
+        "\
+#command echo `echo middle`
+#stdout
+middle
+#status 0
+",
+    );
+}
@@ -0,0 +1,145 @@
+// Cross-process harness for the bundled binaries (`cat`, `echo`, `pwd`,
+// `wc`, ...), modeled on assert_cli: replaces the `get_X_binary_path` +
+// ad-hoc `Command::output()` assertions each binary's own test module used
+// to repeat by hand.
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Locates a bundled binary built by `cargo build --workspace`, handling
+/// the `target/{debug,release}` split and the `deps/` layout `cargo test`
+/// sometimes builds into, once, instead of every binary's test module
+/// re-deriving its own path.
+fn resolve_binary_path(name: &str) -> PathBuf {
+    ["release", "debug"]
+        .into_iter()
+        .flat_map(|profile| {
+            [
+                PathBuf::from(format!("target/{profile}/{name}")),
+                PathBuf::from(format!("target/{profile}/deps/{name}")),
+            ]
+        })
+        .find(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from(format!("target/debug/{name}")))
+}
+
+/// A single bundled-binary invocation under test. Build it up with
+/// `.arg`/`.args`/`.stdin`/`.current_dir`, then `.run()` it and assert on
+/// the captured `Outcome`.
+pub struct CommandUnderTest {
+    command: Command,
+    stdin: Option<Vec<u8>>,
+}
+
+impl CommandUnderTest {
+    /// `name` is the bundled binary's name, e.g. `"echo"` or `"wc"`.
+    pub fn new(name: &str) -> Self {
+        Self {
+            command: Command::new(resolve_binary_path(name)),
+            stdin: None,
+        }
+    }
+
+    pub fn arg(mut self, arg: &str) -> Self {
+        self.command.arg(arg);
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<std::ffi::OsStr>,
+    {
+        self.command.args(args);
+        self
+    }
+
+    pub fn stdin(mut self, input: &str) -> Self {
+        self.stdin = Some(input.as_bytes().to_vec());
+        self
+    }
+
+    pub fn current_dir(mut self, dir: &Path) -> Self {
+        self.command.current_dir(dir);
+        self
+    }
+
+    /// Spawns the binary, feeds it `stdin` (if any), and waits for it to
+    /// finish, capturing stdout/stderr/exit code into an `Outcome`.
+    pub fn run(mut self) -> Outcome {
+        self.command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = self.command.spawn().expect("failed to spawn binary under test");
+        if let Some(input) = self.stdin {
+            child
+                .stdin
+                .take()
+                .expect("stdin was piped")
+                .write_all(&input)
+                .expect("failed to write stdin to binary under test");
+        }
+
+        let output = child
+            .wait_with_output()
+            .expect("failed to wait for binary under test");
+        Outcome {
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            code: output.status.code().unwrap_or(-1),
+        }
+    }
+}
+
+/// A bundled binary's captured result, with fluent assertions so a test
+/// reads as a sentence rather than a block of `assert_eq!`s.
+pub struct Outcome {
+    pub stdout: String,
+    pub stderr: String,
+    pub code: i32,
+}
+
+impl Outcome {
+    pub fn succeeds(&self) -> &Self {
+        assert_eq!(
+            self.code, 0,
+            "expected success, got exit code {} (stderr: {:?})",
+            self.code, self.stderr
+        );
+        self
+    }
+
+    pub fn fails_with(&self, code: i32) -> &Self {
+        assert_eq!(
+            self.code, code,
+            "expected exit code {code}, got {} (stderr: {:?})",
+            self.code, self.stderr
+        );
+        self
+    }
+
+    pub fn stdout_is(&self, expected: &str) -> &Self {
+        assert_eq!(self.stdout, expected);
+        self
+    }
+
+    pub fn stdout_contains(&self, needle: &str) -> &Self {
+        assert!(
+            self.stdout.contains(needle),
+            "expected stdout to contain {needle:?}, got {:?}",
+            self.stdout
+        );
+        self
+    }
+
+    pub fn stderr_is_empty(&self) -> &Self {
+        assert!(
+            self.stderr.is_empty(),
+            "expected empty stderr, got {:?}",
+            self.stderr
+        );
+        self
+    }
+}
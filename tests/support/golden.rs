@@ -0,0 +1,286 @@
+// Declarative golden-file harness: lets a regression case for `wc`,
+// redirection, or a pipeline be added as a plain-text fixture instead of a
+// Rust function. Modeled on the same idea as `WorkDir`/`CommandUnderTest` in
+// this module — replace a hand-written assertion dance with one small
+// reusable runner.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Mutex;
+
+use cli_rust::modules::input::input_processor::pipeline_to_runner_commands;
+use cli_rust::{Environment, InputProcessorBuilder, Runner};
+
+use super::WorkDir;
+
+/// One parsed fixture: a `#command` line to run plus the expected
+/// stdin/stdout/stderr/status and any files to materialize before running
+/// or assert on afterward. See `Fixture::parse` for the directive grammar.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Fixture {
+    command: String,
+    stdin: String,
+    stdout: String,
+    stderr: String,
+    status: i32,
+    infiles: Vec<(String, String)>,
+    outfiles: Vec<(String, String)>,
+}
+
+/// Which block of a fixture the lines following a directive belong to —
+/// `#nonewline` acts on whichever of these was most recently opened.
+enum Block {
+    Stdin,
+    Stdout,
+    Stderr,
+    Infile(usize),
+    Outfile(usize),
+}
+
+impl Fixture {
+    /// Parses a fixture's directives (`#command`, `#stdin`, `#stdout`,
+    /// `#stderr`, `#infile NAME`, `#outfile NAME`, `#status N`,
+    /// `#nonewline`) and the literal text blocks that follow them. Every
+    /// non-directive line is appended, newline included, to whichever block
+    /// is currently open; `#nonewline` strips the trailing newline just
+    /// added by the block it immediately follows. A line outside any block
+    /// (before the first directive, or right after `#command`/`#status`) is
+    /// ignored rather than rejected, so a fixture can carry a leading
+    /// comment line.
+    fn parse(text: &str) -> Self {
+        let mut fixture = Fixture {
+            status: 0,
+            ..Default::default()
+        };
+        let mut open: Option<Block> = None;
+
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("#command ") {
+                fixture.command = rest.to_string();
+                open = None;
+            } else if line == "#stdin" {
+                open = Some(Block::Stdin);
+            } else if line == "#stdout" {
+                open = Some(Block::Stdout);
+            } else if line == "#stderr" {
+                open = Some(Block::Stderr);
+            } else if let Some(name) = line.strip_prefix("#infile ") {
+                fixture.infiles.push((name.to_string(), String::new()));
+                open = Some(Block::Infile(fixture.infiles.len() - 1));
+            } else if let Some(name) = line.strip_prefix("#outfile ") {
+                fixture.outfiles.push((name.to_string(), String::new()));
+                open = Some(Block::Outfile(fixture.outfiles.len() - 1));
+            } else if let Some(rest) = line.strip_prefix("#status ") {
+                fixture.status = rest.trim().parse().expect("invalid #status value");
+                open = None;
+            } else if line == "#nonewline" {
+                if let Some(buf) = fixture.block_mut(&open) {
+                    buf.pop();
+                }
+            } else if let Some(buf) = fixture.block_mut(&open) {
+                buf.push_str(line);
+                buf.push('\n');
+            }
+        }
+
+        fixture
+    }
+
+    fn block_mut(&mut self, open: &Option<Block>) -> Option<&mut String> {
+        match open {
+            Some(Block::Stdin) => Some(&mut self.stdin),
+            Some(Block::Stdout) => Some(&mut self.stdout),
+            Some(Block::Stderr) => Some(&mut self.stderr),
+            Some(Block::Infile(i)) => Some(&mut self.infiles[*i].1),
+            Some(Block::Outfile(i)) => Some(&mut self.outfiles[*i].1),
+            None => None,
+        }
+    }
+}
+
+/// Serializes the `std::env::set_current_dir` calls `run_fixture` makes —
+/// the process's working directory is global state, so two fixtures
+/// running on separate test threads must not chdir concurrently. A fixture
+/// needs the chdir at all because `InputProcessor`/`Runner` resolve every
+/// relative redirection path against the process's current directory, not
+/// a directory a caller can hand them directly.
+static CWD_LOCK: Mutex<()> = Mutex::new(());
+
+/// Restores the original working directory on drop, including when the
+/// comparisons in `run_fixture` panic.
+struct CwdGuard(PathBuf);
+
+impl Drop for CwdGuard {
+    fn drop(&mut self) {
+        let _ = std::env::set_current_dir(&self.0);
+    }
+}
+
+/// Runs `cmd` — the text inside a fixture's `$(...)`/backtick command
+/// substitution — through its own tokenize/expand/produce pass and executes
+/// it via `runner`, the same way `Repl::run_command_substitution` services
+/// `InputProcessorBuilder::with_executor` for a real session.
+fn run_command_substitution(runner: &Rc<RefCell<Runner>>, cmd: &str) -> std::result::Result<String, String> {
+    let mut env = Environment::with_vars(
+        runner
+            .borrow()
+            .env_vars()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+    );
+    let pipeline = InputProcessorBuilder::new()
+        .build()
+        .process(cmd, &mut env)
+        .map_err(|e| e.to_string())?;
+    let mut commands = pipeline_to_runner_commands(pipeline).map_err(|e| e.to_string())?;
+
+    if commands.is_empty() {
+        return Ok(String::new());
+    }
+    if commands.len() == 1 {
+        let command = commands.remove(0);
+        runner.borrow_mut().execute(command).map_err(|e| e.to_string())
+    } else {
+        runner.borrow().execute_pipeline(commands).map_err(|e| e.to_string())
+    }
+}
+
+/// Parses `fixture_text`, runs its `#command` against a fresh `WorkDir`, and
+/// panics with a readable diff if stdout, stderr, exit status, or any
+/// declared `#outfile` doesn't match what the fixture expects. A `#command`
+/// containing `$(...)` or backtick command substitution is serviced the same
+/// way the REPL services it — see `run_command_substitution`.
+///
+/// A single-stage `#command` captures stderr via `Runner::execute_outcome`;
+/// a `|`-chained one falls back to `execute_pipeline_outcome`, which (like
+/// the REPL it backs) always reports empty stderr — a multi-stage fixture's
+/// `#stderr` block should stay empty until that's fixed.
+pub fn run_fixture(fixture_text: &str) {
+    let fixture = Fixture::parse(fixture_text);
+    let dir = WorkDir::new("golden");
+    for (name, content) in &fixture.infiles {
+        dir.create(name, content);
+    }
+
+    let _lock = CWD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let original_cwd = std::env::current_dir().expect("failed to read current dir");
+    let _restore = CwdGuard(original_cwd.clone());
+    std::env::set_current_dir(dir.path()).expect("failed to chdir into work dir");
+
+    let runner = Rc::new(RefCell::new(Runner::new(
+        original_cwd.join("target/release"),
+        HashMap::new(),
+    )));
+    let substitution_runner = Rc::clone(&runner);
+    let ip = InputProcessorBuilder::new()
+        .with_executor(move |cmd| run_command_substitution(&substitution_runner, cmd))
+        .build();
+
+    let mut env = Environment::new();
+    let pipeline = ip
+        .process(&fixture.command, &mut env)
+        .unwrap_or_else(|e| panic!("failed to parse `#command {}`: {e}", fixture.command));
+    let mut commands = pipeline_to_runner_commands(pipeline)
+        .unwrap_or_else(|e| panic!("failed to resolve redirections for `#command {}`: {e}", fixture.command));
+    assert!(
+        !commands.is_empty(),
+        "`#command {}` produced no stages to run",
+        fixture.command
+    );
+    if !fixture.stdin.is_empty() {
+        commands[0] = commands[0].clone().with_stdin(fixture.stdin.clone());
+    }
+
+    let outcome = if commands.len() == 1 {
+        runner
+            .borrow_mut()
+            .execute_outcome(commands.into_iter().next().unwrap())
+            .unwrap_or_else(|e| panic!("failed to run `#command {}`: {e}", fixture.command))
+    } else {
+        runner
+            .borrow()
+            .execute_pipeline_outcome(commands)
+            .unwrap_or_else(|e| panic!("failed to run `#command {}`: {e}", fixture.command))
+    };
+
+    assert_eq!(
+        outcome.stdout, fixture.stdout,
+        "stdout mismatch for `#command {}`:\n--- expected ---\n{}--- actual ---\n{}",
+        fixture.command, fixture.stdout, outcome.stdout
+    );
+    assert_eq!(
+        outcome.stderr, fixture.stderr,
+        "stderr mismatch for `#command {}`:\n--- expected ---\n{}--- actual ---\n{}",
+        fixture.command, fixture.stderr, outcome.stderr
+    );
+    assert_eq!(
+        outcome.code.unwrap_or(-1),
+        fixture.status,
+        "exit status mismatch for `#command {}`: expected {}, got {:?}",
+        fixture.command,
+        fixture.status,
+        outcome.code
+    );
+
+    for (name, expected) in &fixture.outfiles {
+        let actual = dir.read(name);
+        assert_eq!(
+            &actual, expected,
+            "#outfile {name} mismatch for `#command {}`:\n--- expected ---\n{}--- actual ---\n{}",
+            fixture.command, expected, actual
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_command_and_inline_blocks() {
+        let fixture = Fixture::parse("#command echo hi\n#stdout\nhi\n#status 0\n");
+        assert_eq!(fixture.command, "echo hi");
+        assert_eq!(fixture.stdout, "hi\n");
+        assert_eq!(fixture.status, 0);
+    }
+
+    #[test]
+    fn nonewline_strips_the_trailing_newline_of_the_current_block() {
+        let fixture = Fixture::parse("#command printf hi\n#stdout\nhi\n#nonewline\n");
+        assert_eq!(fixture.stdout, "hi");
+    }
+
+    #[test]
+    fn parses_infiles_and_outfiles_by_name() {
+        let fixture = Fixture::parse("#command cat a.txt\n#infile a.txt\nhello\n#outfile b.txt\nhello\n");
+        assert_eq!(fixture.infiles, vec![("a.txt".to_string(), "hello\n".to_string())]);
+        assert_eq!(fixture.outfiles, vec![("b.txt".to_string(), "hello\n".to_string())]);
+    }
+
+    #[test]
+    fn defaults_status_to_zero_when_not_declared() {
+        let fixture = Fixture::parse("#command true\n");
+        assert_eq!(fixture.status, 0);
+    }
+
+    #[test]
+    fn runs_a_simple_echo_fixture_end_to_end() {
+        run_fixture("#command echo hello\n#stdout\nhello\n#status 0\n");
+    }
+
+    #[test]
+    fn runs_a_redirection_fixture_with_an_infile_and_outfile() {
+        run_fixture("#command cat < in.txt > out.txt\n#infile in.txt\nhello world\n#outfile out.txt\nhello world\n");
+    }
+
+    #[test]
+    fn runs_a_pipeline_fixture() {
+        run_fixture("#command echo hello world | wc -w\n#stdout\n2\n#status 0\n");
+    }
+
+    #[test]
+    fn runs_a_command_substitution_fixture() {
+        run_fixture("#command echo $(echo inner)\n#stdout\ninner\n#status 0\n");
+    }
+}
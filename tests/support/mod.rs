@@ -0,0 +1,126 @@
+// Shared integration-test harness, modeled on ripgrep's `WorkDir` and
+// coreutils' `CommandUnderTest`: replaces the temp-dir-create/write/read/
+// clean-up dance every test file in this directory used to repeat by hand.
+pub mod cli;
+pub mod golden;
+pub use cli::CommandUnderTest;
+pub use golden::run_fixture;
+
+use cli_rust::modules::command::Command;
+use cli_rust::modules::runner::Runner;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{fs, process};
+
+static WORK_DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A uniquely-named scratch directory under the system temp dir, removed on
+/// `Drop`. Each instance gets its own directory (via a global atomic
+/// counter plus the process id) so tests stay parallel-safe.
+pub struct WorkDir {
+    path: PathBuf,
+}
+
+impl WorkDir {
+    /// Creates a fresh, empty directory named `cli_test_<name>_<pid>_<n>`.
+    pub fn new(name: &str) -> Self {
+        let n = WORK_DIR_COUNTER.fetch_add(1, Ordering::SeqCst);
+        let path = std::env::temp_dir().join(format!(
+            "cli_test_{name}_{}_{n}",
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("failed to create work dir");
+        Self { path }
+    }
+
+    /// The directory's path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Joins `name` onto this directory's path.
+    pub fn join(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+
+    /// Writes `contents` to `name` inside this directory.
+    pub fn create(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.join(name);
+        fs::write(&path, contents).expect("failed to write work dir fixture");
+        path
+    }
+
+    /// Reads `name` from inside this directory as a string.
+    pub fn read(&self, name: &str) -> String {
+        fs::read_to_string(self.join(name)).expect("failed to read work dir file")
+    }
+
+    /// True if `name` exists inside this directory.
+    pub fn exists(&self, name: &str) -> bool {
+        self.join(name).exists()
+    }
+
+    /// Builds a `Runner` rooted at `target/release` with `env_vars`, for
+    /// resolving this repo's custom binaries the same way the REPL does.
+    pub fn runner(&self, env_vars: HashMap<String, String>) -> Runner {
+        Runner::new(PathBuf::from("target/release"), env_vars)
+    }
+
+    /// Runs `cmd` through a `target/release`-rooted `Runner` and captures
+    /// the outcome.
+    pub fn run(&self, cmd: Command) -> Outcome {
+        let mut runner = self.runner(HashMap::new());
+        match runner.execute(cmd) {
+            Ok(stdout) => Outcome {
+                stdout,
+                error: None,
+            },
+            Err(e) => Outcome {
+                stdout: String::new(),
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+impl Drop for WorkDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// The captured result of running a `Command` through a `WorkDir`'s runner.
+pub struct Outcome {
+    pub stdout: String,
+    pub error: Option<String>,
+}
+
+impl Outcome {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    pub fn assert_stdout_contains(&self, needle: &str) -> &Self {
+        assert!(
+            self.stdout.contains(needle),
+            "expected stdout to contain {needle:?}, got {:?}",
+            self.stdout
+        );
+        self
+    }
+
+    pub fn assert_error_contains(&self, needle: &str) -> &Self {
+        let error = self
+            .error
+            .as_ref()
+            .unwrap_or_else(|| panic!("expected an error, command succeeded with stdout {:?}", self.stdout));
+        assert!(
+            error.contains(needle),
+            "expected error to contain {needle:?}, got {:?}",
+            error
+        );
+        self
+    }
+}